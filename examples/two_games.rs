@@ -0,0 +1,59 @@
+//! Two games side by side: the left one is played with the keyboard ('w'/'s' and the arrow keys),
+//! in the right one two bots play against each other.
+use bevy::prelude::*;
+use bevy_pong::*;
+
+/// Marks the game played by the bots.
+#[derive(Component)]
+struct BotGame;
+
+fn setup(mut commands: Commands) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+
+    let mut options = PongOptions {
+        // The default score display needs the font in "assets/fonts".
+        score_display_options: None,
+        ..Default::default()
+    };
+    options.game.size = Vec2::new(600., 400.);
+    options.game.position = Vec3::new(-320., 0., 0.);
+    commands.spawn_bundle(PongGameBundle::new(options.clone()));
+
+    options.game.position = Vec3::new(320., 0., 0.);
+    options.player.controllers = (Controller::External, Controller::External);
+    commands
+        .spawn_bundle(PongGameBundle::new(options))
+        .insert(PlayerInput::default())
+        .insert(BotGame);
+}
+
+/// Moves both paddles of the bot game towards the ball.
+fn move_bots(
+    mut games: Query<(Entity, &mut PlayerInput), With<BotGame>>,
+    balls: Query<(&Parent, &Transform), With<Ball>>,
+    players: Query<(&Parent, &Player, &Transform)>,
+) {
+    for (game, mut input) in games.iter_mut() {
+        let ball_y = match balls.iter().find(|(parent, _)| parent.0 == game) {
+            Some((_, transform)) => transform.translation.y,
+            None => continue,
+        };
+        for (_, player, transform) in players.iter().filter(|(parent, _, _)| parent.0 == game) {
+            let direction = ((ball_y - transform.translation.y) / 10.).clamp(-1., 1.);
+            match player {
+                Player::Player1 => input.0 = direction,
+                Player::Player2 => input.1 = direction,
+            }
+        }
+    }
+}
+
+fn main() {
+    App::new()
+        .insert_resource(WindowDescriptor { width: 1280., height: 480., ..Default::default() })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(PongPlugin::new().without_default_game())
+        .add_startup_system(setup)
+        .add_system(move_bots.label(PongSystem::PreInput))
+        .run();
+}
@@ -1,16 +1,212 @@
+use std::{
+    sync::Mutex,
+    time::Duration,
+};
+
 use bevy::{
+    core::{FixedTimestep, FixedTimesteps},
+    ecs::system::{CommandQueue, EntityCommands, SystemParam, SystemState},
     prelude::*,
-    sprite::collide_aabb::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+    window::WindowFocused,
 };
 
+/// How the background of the game gets rendered.
+#[derive(Clone)]
+pub enum Background {
+    Color(Color),
+    /// The texture gets stretched to the size of the game.
+    Texture(Handle<Image>),
+    /// No background gets rendered, so whatever is behind the game stays visible.
+    None,
+}
+
+/// Determines what happens when the ball reaches the left or right edge of the game.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ScoreMode {
+    /// The opposite player scores a point and the ball gets reset.
+    Goals,
+    /// The ball reappears on the opposite side without anyone scoring.
+    WrapAround,
+    /// The ball bounces off the edge like off the top and bottom.
+    Bounce,
+}
+
+/// Determines which player gets the point for a goal.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ScoreAttribution {
+    /// The player opposite the goal.
+    ByGoalSide,
+    /// The player who last touched the ball, falls back to `ByGoalSide` if nobody touched it
+    /// since the serve. This allows own goals.
+    ByLastTouch,
+}
+
+/// Determines what happens after a player won the game.
+#[derive(Copy, Clone, PartialEq)]
+pub enum AfterGameOver {
+    /// Everything stays stopped.
+    Freeze,
+    /// The game gets reset after the delay (in seconds).
+    AutoRestart { delay: f32 },
+    /// The game gets reset as soon as the key is pressed.
+    WaitForKey { key: KeyCode },
+}
+
+/// A frame drawn around the game, outside of the court.
+#[derive(Copy, Clone)]
+pub struct BorderOptions {
+    pub thickness: f32,
+    pub color: Color,
+}
+
+impl BorderOptions {
+    /// The positions and sizes of the top, bottom, left and right side of the border.
+    fn sides(&self, game_size: Vec2) -> [(Vec2, Vec2); 4] {
+        let offset = (game_size + Vec2::splat(self.thickness)) / 2.;
+        let horizontal = Vec2::new(game_size.x + 2. * self.thickness, self.thickness);
+        let vertical = Vec2::new(self.thickness, game_size.y);
+        [
+            (Vec2::new(0., offset.y), horizontal),
+            (Vec2::new(0., -offset.y), horizontal),
+            (Vec2::new(-offset.x, 0.), vertical),
+            (Vec2::new(offset.x, 0.), vertical),
+        ]
+    }
+}
+
+/// A shadow rendered under every ball.
 #[derive(Copy, Clone)]
+pub struct ShadowOptions {
+    /// The offset of the shadow relative to the ball.
+    pub offset: Vec2,
+    /// The color of the shadow, usually dark and semi-transparent.
+    pub color: Color,
+}
+
+/// Determines which goals count, if several balls reach a goal in the same step.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MultiGoalMode {
+    /// Every goal counts, in case of goals on both sides each player gets a point.
+    AwardAll,
+    /// Only the goal of the ball which crossed its goal line first counts, the other balls are
+    /// served again without a point.
+    FirstOnly,
+}
+
+/// Where the ball gets served from after a goal.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ServeOrigin {
+    /// From `BallOptions::start_position`.
+    Center,
+    /// From the height of the scorer's paddle (horizontally still from the start position).
+    ScorerPaddle,
+}
+
+/// The orientation of the game on the screen.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Orientation {
+    /// Player 1 on the left and player 2 on the right.
+    Horizontal,
+    /// Player 1 at the bottom and player 2 at the top, the up keys move the paddles to the left
+    /// and the down keys to the right. All sizes and positions (except `GameOptions::position`)
+    /// stay relative to the court, e.g. `GameOptions::size.x` is the distance between the goals.
+    Vertical,
+}
+
+impl Orientation {
+    /// The rotation of the game relative to a horizontal game.
+    pub fn rotation(&self) -> Quat {
+        match self {
+            Orientation::Horizontal => Quat::IDENTITY,
+            Orientation::Vertical => Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct GameOptions {
     pub size: Vec2,
     /// Center position of the game, players and ball are placed relative to this
     /// position and with a z-Coordinate which is 1 higher.
     pub position: Vec3,
-    /// The background color for the entire game.
-    pub background: Color,
+    /// The background for the entire game.
+    pub background: Background,
+    /// What happens if the ball reaches the left or right edge; `ScoreMode::WrapAround` and
+    /// `ScoreMode::Bounce` result in an endless game.
+    pub score_mode: ScoreMode,
+    /// Which player gets the point for a goal.
+    pub score_attribution: ScoreAttribution,
+    /// If true, the ball waits for any player to press their up key before the first serve, the
+    /// game (e.g. the `time_limit` or the `escalation`) doesn't run until then.
+    pub wait_for_ready: bool,
+    /// Replaces player 1 (the left) with a solid wall, which reflects the ball; no goals can be
+    /// scored on this side.
+    pub left_wall: bool,
+    /// Replaces player 2 (the right) with a solid wall, see `left_wall`.
+    pub right_wall: bool,
+    /// Solo practice: only player 1 plays, player 2's side is a wall (like with `right_wall`).
+    pub single_player: bool,
+    /// Optional zone in the center of the game (half-width, factor), in which the ball moves
+    /// slower by the given factor. The velocity of the ball itself is not changed, so it
+    /// regains its speed once it leaves the zone.
+    pub center_slow: Option<(f32, f32)>,
+    /// If set, a `ScoreMilestoneEvent` is sent every time a players score reaches a multiple of it.
+    pub milestone: Option<u16>,
+    /// If set, the game is over as soon as a player reaches this score; the balls and paddles stop
+    /// moving afterwards.
+    pub win_score: Option<u16>,
+    /// What happens after a player reached the `win_score`.
+    pub after_game_over: AfterGameOver,
+    /// If set, a ball reaching a goal line at an angle (in radians, relative to the goal line)
+    /// below this value bounces off instead of scoring.
+    pub save_angle: Option<f32>,
+    /// If set, the serving player changes every given number of points (starting with player
+    /// 1), the ball is always served towards the opponent of the serving player.
+    pub serve_rotation: Option<u16>,
+    /// If true, a short burst of particles is spawned at the goal line on every goal.
+    pub goal_particles: bool,
+    /// If true, the background briefly flashes in the color of the scorer on every goal.
+    pub goal_flash: bool,
+    /// If true, a small mark fades out at the point where the ball hit a paddle.
+    pub impact_marks: bool,
+    /// If set, a shadow follows every ball.
+    pub ball_shadow: Option<ShadowOptions>,
+    /// If set, an additional ball is spawned every time a rally lasts this many seconds longer,
+    /// to prevent endless rallies. The number of balls is still limited by `BallOptions::max_balls`.
+    pub escalation: Option<f32>,
+    /// The orientation of the game, e.g. `Orientation::Vertical` for portrait screens.
+    pub orientation: Orientation,
+    /// If true, the ball wraps from the top to the bottom (and vice versa) instead of bouncing.
+    pub wrap_vertically: bool,
+    /// If true, the game is paused (see `PongPaused`) while the window is unfocused.
+    pub pause_on_focus_loss: bool,
+    /// Time (in seconds) the ball keeps flying after a goal before it gets reset, the point is
+    /// scored immediately nonetheless.
+    pub goal_delay: f32,
+    /// If set, a `StreakEvent` is sent for every point of a player who scored at least this many
+    /// points in a row.
+    pub streak_threshold: Option<u16>,
+    /// If set, the paddle of a player on a streak (see `streak_threshold`) is tinted with this color.
+    pub streak_color: Option<Color>,
+    /// If set, a frame is drawn around the game.
+    pub border: Option<BorderOptions>,
+    /// If true, the rendered positions of the balls and paddles are interpolated between the
+    /// physics steps, so they move smoothly with `PhysicsSchedule::FixedTimestep`. The rendering
+    /// lags one step behind the physics in exchange.
+    pub interpolate: bool,
+    /// If set, the game is over after this many seconds and the player with the higher score wins.
+    pub time_limit: Option<f32>,
+    /// What happens if the scores are tied when the `time_limit` is reached: if true, the game
+    /// goes into overtime (see `Overtime`) and the next point wins the game (a golden goal),
+    /// otherwise a `DrawEvent` is sent and the game is over without a winner.
+    pub sudden_death: bool,
+    /// What happens if several balls reach a goal in the same step.
+    pub multi_goal_mode: MultiGoalMode,
+    /// Where the ball gets served from after a goal.
+    pub serve_origin: ServeOrigin,
 }
 
 impl Default for GameOptions {
@@ -18,11 +214,67 @@ impl Default for GameOptions {
         Self {
             size: Vec2::new(600., 400.),
             position: Vec3::default(),
-            background: Color::BLACK,
+            background: Background::Color(Color::BLACK),
+            score_mode: ScoreMode::Goals,
+            score_attribution: ScoreAttribution::ByGoalSide,
+            wait_for_ready: false,
+            left_wall: false,
+            right_wall: false,
+            single_player: false,
+            center_slow: None,
+            milestone: None,
+            win_score: None,
+            after_game_over: AfterGameOver::Freeze,
+            save_angle: None,
+            serve_rotation: None,
+            goal_particles: false,
+            goal_flash: false,
+            impact_marks: false,
+            ball_shadow: None,
+            escalation: None,
+            orientation: Orientation::Horizontal,
+            wrap_vertically: false,
+            pause_on_focus_loss: false,
+            goal_delay: 0.,
+            streak_threshold: None,
+            streak_color: None,
+            border: None,
+            interpolate: false,
+            time_limit: None,
+            sudden_death: false,
+            multi_goal_mode: MultiGoalMode::AwardAll,
+            serve_origin: ServeOrigin::Center,
         }
     }
 }
 
+/// Determines which paddles are moved back to their start position after a goal.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum RecenterMode {
+    Both,
+    None,
+    ScorerOnly,
+    ConcederOnly,
+}
+
+/// How the paddles move while their keys are pressed.
+#[derive(Copy, Clone, PartialEq)]
+pub enum MovementMode {
+    /// Smoothly with `PlayerOptions::speed`.
+    Continuous,
+    /// By `step` per key press; while the key is held, another step is made every `cooldown` seconds.
+    Stepped { step: f32, cooldown: f32 },
+}
+
+/// Who moves a paddle.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Controller {
+    /// The player, with the keys from the `PlayerOptions`.
+    Keyboard,
+    /// A bot or a test, by setting the direction in the `PlayerInput` resource.
+    External,
+}
+
 #[derive(Copy, Clone)]
 pub struct PlayerOptions {
     /// The colors for the players (colors.0 is for player 1; colors.1 is for player 2).
@@ -33,6 +285,37 @@ pub struct PlayerOptions {
     /// Up and down keys to control player two (the right).
     pub player2_keys: (KeyCode, KeyCode),
     pub speed: f32,
+    /// Vertical offsets of the start positions (start_offsets.0 is for player 1; start_offsets.1 is
+    /// for player 2), the paddles are also moved there after each goal.
+    pub start_offsets: (f32, f32),
+    /// Which paddles are moved back to their start position after a goal.
+    pub recenter_on_goal: RecenterMode,
+    /// If false, the velocity of all paddles is set to zero after a goal, so they don't drift.
+    pub keep_momentum_on_goal: bool,
+    /// Optional dash keys (dash_keys.0 is for player 1; dash_keys.1 is for player 2), which
+    /// briefly multiply the speed of the paddle by `dash_multiplier`.
+    pub dash_keys: (Option<KeyCode>, Option<KeyCode>),
+    pub dash_multiplier: f32,
+    /// The time (in seconds) after a dash, until the player can dash again.
+    pub dash_cooldown: f32,
+    /// Who moves the paddles (controllers.0 is for player 1; controllers.1 is for player 2).
+    pub controllers: (Controller, Controller),
+    pub movement_mode: MovementMode,
+    /// If set (threshold, bonus factor), a ball hitting the front of a paddle within the threshold
+    /// (vertical distance between the centers) gets its velocity multiplied by the bonus factor
+    /// and a `PerfectHitEvent` is sent.
+    pub perfect_hit: Option<(f32, f32)>,
+    /// Optional keys (center_keys.0 is for player 1; center_keys.1 is for player 2), which move
+    /// the paddle back to the center while held and no movement key is pressed.
+    pub center_keys: (Option<KeyCode>, Option<KeyCode>),
+    /// If true, the center keys move the paddle to the center instantly instead of with `speed`.
+    pub center_instantly: bool,
+    /// If true, the ball only bounces off the side of a paddle facing the court, it passes
+    /// through the back, top and bottom of the paddle.
+    pub front_face_only: bool,
+    /// The radius of the rounded corners of the rendered paddles, 0 for sharp corners. The
+    /// collisions still use the sharp rectangle.
+    pub corner_radius: f32,
 }
 
 impl Default for PlayerOptions {
@@ -43,20 +326,143 @@ impl Default for PlayerOptions {
             player1_keys: (KeyCode::W, KeyCode::S),
             player2_keys: (KeyCode::Up, KeyCode::Down),
             speed: 200.,
+            start_offsets: (0., 0.),
+            recenter_on_goal: RecenterMode::Both,
+            keep_momentum_on_goal: false,
+            dash_keys: (None, None),
+            dash_multiplier: 3.,
+            dash_cooldown: 1.,
+            controllers: (Controller::Keyboard, Controller::Keyboard),
+            movement_mode: MovementMode::Continuous,
+            perfect_hit: None,
+            center_keys: (None, None),
+            center_instantly: false,
+            front_face_only: false,
+            corner_radius: 0.,
         }
     }
 }
 
-#[derive(Copy, Clone)]
+/// The shape of the ball used for collisions.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum BallShape {
+    Rectangle,
+    /// A hexagon with flat top and bottom, fitted into the width of the ball. Its pointed
+    /// left and right sides deflect the ball vertically when hitting a paddle off-center.
+    Puck,
+}
+
+impl BallShape {
+    /// Size of the box used for collisions of a ball with the given size.
+    pub fn collision_size(&self, size: Vec2) -> Vec2 {
+        match self {
+            BallShape::Rectangle => size,
+            BallShape::Puck => Vec2::new(size.x, size.y.min(size.x * PUCK_HEIGHT_RATIO)),
+        }
+    }
+}
+
+/// Height of a regular hexagon with flat top and bottom relative to its width.
+const PUCK_HEIGHT_RATIO: f32 = 0.866;
+/// Part of the horizontal speed which gets turned into vertical speed when a puck hits a paddle off-center.
+const PUCK_DEFLECTION: f32 = 0.25;
+
+/// Determines when the ball gets faster.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SpeedupMode {
+    /// Periodically, every `BallOptions::speedup_time` seconds.
+    Timed,
+    /// Every time the ball hits a paddle.
+    PerHit,
+    Both,
+}
+
+#[derive(Clone)]
 pub struct BallOptions {
     pub color: Color,
+    /// The size of the rendered ball, see `shape` for the size used for collisions.
     pub size: Vec2,
+    /// The shape of the ball, which only affects the collisions (the ball is rendered as rectangle, see `corner_radius`).
+    pub shape: BallShape,
+    /// The radius of the rounded corners of the rendered ball, 0 for sharp corners and half the
+    /// size for a round ball. Like `shape`, the collisions aren't affected.
+    pub corner_radius: f32,
+    /// The position (relative to the center of the game) at which the ball starts and gets reset to.
+    pub start_position: Vec2,
     /// Function which gets used to get the velocity with which the ball should start.
     pub start_velocity: fn() -> Vec2,
-    /// The factor by which the velocity gets multiplied periodically.
+    /// If set, the velocity of the ball is rotated by the returned angle (in radians) after every
+    /// bounce off the top or bottom wall, e.g. a small random angle for unpredictable rallies.
+    pub wall_randomness: Option<fn() -> f32>,
+    /// When the ball gets faster.
+    pub speedup_mode: SpeedupMode,
+    /// The factor by which the velocity gets multiplied on every speedup.
     pub speedup_factor: f32,
-    /// The period (in seconds) the balls velocity gets incremented.
+    /// Phases of the speedup as (time threshold, factor): once the game has been running for the
+    /// threshold (in seconds, see `MatchElapsed`), the factor replaces `speedup_factor` until the
+    /// next threshold is reached, e.g. `vec![(30., 1.2)]` for a faster speedup after 30 seconds.
+    /// The phases may be in any order, `with_speedup_schedule` sorts them. If empty, only
+    /// `speedup_factor` is used.
+    pub speedup_schedule: Vec<(f32, f32)>,
+    /// The period (in seconds) the balls velocity gets incremented, if the speedup is timed.
     pub speedup_time: f32,
+    /// The maximum speed of the ball, the speedup stops at this speed.
+    pub max_speed: Option<f32>,
+    /// The minimum speed of a moving ball, e.g. so the friction can't stall it. A ball standing
+    /// still (e.g. while waiting for the first serve) is not affected.
+    pub min_speed: Option<f32>,
+    /// If set, the ball accelerates smoothly to its full speed within this duration (in seconds)
+    /// after each serve, instead of starting with full speed.
+    pub serve_ramp: Option<f32>,
+    /// The maximum number of balls in the game at the same time; requests to spawn more balls are rejected.
+    pub max_balls: usize,
+    /// The fraction of its velocity the ball loses per second; `0.` means no friction.
+    pub friction: f32,
+    /// If set (growth per hit, max size), the ball grows by the given value every time it hits a
+    /// paddle, up to the max size; it gets its original size back after a goal.
+    pub rally_grow: Option<(f32, f32)>,
+    /// The minimum time (in seconds) between two bounces of a ball off the top or bottom wall,
+    /// 0 (the default) means no limit. A ball reaching a wall again within this time is only pushed
+    /// back into the game, so e.g. with 0.05 a ball which is too large for the game doesn't flip its
+    /// direction every step.
+    pub wall_bounce_interval: f32,
+}
+
+impl BallOptions {
+    pub fn collision_size(&self) -> Vec2 {
+        self.shape.collision_size(self.size)
+    }
+    /// Sets the `speedup_schedule`, sorted by the thresholds; phases with an invalid (NaN)
+    /// threshold are dropped.
+    pub fn with_speedup_schedule(mut self, schedule: impl Into<Vec<(f32, f32)>>) -> Self {
+        let mut schedule = schedule.into();
+        schedule.retain(|(threshold, _)| !threshold.is_nan());
+        schedule.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        self.speedup_schedule = schedule;
+        self
+    }
+    /// The speedup factor after the game has been running for `elapsed` seconds, see `speedup_schedule`.
+    pub fn speedup_factor_at(&self, elapsed: f32) -> f32 {
+        // The phase with the latest threshold which is already reached, so the order doesn't matter.
+        self.speedup_schedule.iter()
+            .filter(|(threshold, _)| *threshold <= elapsed)
+            .fold(None, |latest: Option<&(f32, f32)>, phase| match latest {
+                Some(latest) if latest.0 > phase.0 => Some(latest),
+                _ => Some(phase),
+            })
+            .map_or(self.speedup_factor, |(_, factor)| *factor)
+    }
+    /// Limits the length of the velocity to `min_speed` and `max_speed`.
+    pub fn clamp_speed(&self, velocity: Vec2) -> Vec2 {
+        let velocity = match self.max_speed {
+            Some(max_speed) => velocity.clamp_length_max(max_speed),
+            None => velocity,
+        };
+        match self.min_speed {
+            Some(min_speed) if velocity != Vec2::ZERO => velocity.clamp_length_min(min_speed),
+            _ => velocity,
+        }
+    }
 }
 
 impl Default for BallOptions {
@@ -64,31 +470,105 @@ impl Default for BallOptions {
         Self {
             color: Color::WHITE,
             size: Vec2::new(15., 15.),
+            shape: BallShape::Rectangle,
+            corner_radius: 0.,
+            start_position: Vec2::ZERO,
             start_velocity: || Vec2::new(30., 15.),
+            wall_randomness: None,
+            speedup_mode: SpeedupMode::Timed,
             speedup_factor: 1.1,
+            speedup_schedule: Vec::new(),
             speedup_time: 1.5,
+            max_speed: None,
+            min_speed: None,
+            serve_ramp: None,
+            max_balls: 1,
+            friction: 0.,
+            rally_grow: None,
+            wall_bounce_interval: 0.,
         }
     }
 }
 
-#[derive(Copy, Clone)]
+/// How the scores are displayed.
+#[derive(Clone)]
+pub enum ScoreDisplay {
+    /// As text, using the font options.
+    Text,
+    /// As sprites from a texture atlas, in which the index of each digit's image is the digit itself
+    /// (index 0 shows a 0, index 1 shows a 1, ...). Each digit is rendered with the given size.
+    Images {
+        atlas: Handle<TextureAtlas>,
+        digit_size: Vec2,
+    },
+}
+
+#[derive(Clone)]
 pub struct ScoreDisplayOptions {
-    font_path: &'static str,
-    font_size: f32,
-    font_color: Color,
+    /// Whether the scores are displayed as text or images; the font options only apply to text.
+    pub display: ScoreDisplay,
+    pub font_path: &'static str,
+    pub font_size: f32,
+    pub font_color: Color,
+    /// If set, the score of a player at match point (see `GameOptions::win_score`) is highlighted with this color.
+    pub match_point_color: Option<Color>,
+    /// Whether the speed of the fastest ball is displayed as text right below the score, using the
+    /// font options.
+    pub show_ball_speed: bool,
+    /// If true, the score of each player is displayed in the color of their paddle instead of
+    /// `font_color` (score images are tinted with it).
+    pub color_per_player: bool,
+    /// If true and `GameOptions::win_score` is set, the scores count down to 0 (the points still
+    /// missing to win), e.g. like lives.
+    pub count_down: bool,
+    /// If true, a score briefly pops up in size when it changes (only for the text display).
+    pub score_animation: bool,
+    /// If true, the score of the winner bounces and the score of the loser is dimmed while the
+    /// game is over (only for the text display).
+    pub win_celebration: bool,
+    /// If set, the separator between the scores is highlighted with this color during the
+    /// overtime (see `GameOptions::sudden_death`).
+    pub overtime_color: Option<Color>,
 }
 
 impl Default for ScoreDisplayOptions {
     fn default() -> Self {
         Self {
+            display: ScoreDisplay::Text,
             font_path: "fonts/FiraMono-Medium.ttf",
             font_size: 20.,
             font_color: Color::WHITE,
+            match_point_color: None,
+            show_ball_speed: false,
+            color_per_player: false,
+            count_down: false,
+            score_animation: false,
+            win_celebration: false,
+            overtime_color: None,
         }
     }
 }
 
-#[derive(Copy, Clone)]
+impl ScoreDisplayOptions {
+    /// The height of the score (text or digit images) at the top of the game.
+    fn score_height(&self) -> f32 {
+        match self.display {
+            ScoreDisplay::Text => self.font_size,
+            ScoreDisplay::Images { digit_size, .. } => digit_size.y,
+        }
+    }
+
+    /// The y coordinate of the ball speed text, which sits right below the score.
+    fn ball_speed_y(&self, game_size: Vec2) -> f32 {
+        let score_y = game_size.y / 2. - self.score_height() * (2. / 3.);
+        score_y - (self.score_height() + self.font_size) / 2.
+    }
+}
+
+/// The options of a game, stored as component on its `PongGame` entity. The `PongOptions`
+/// resource holds the options of the game spawned by the plugin at startup (see
+/// `PongPlugin::without_default_game`), changes to the resource are copied to that game.
+#[derive(Clone, Component)]
 pub struct PongOptions {
     pub game: GameOptions,
     pub player: PlayerOptions,
@@ -109,12 +589,36 @@ impl Default for PongOptions {
 }
 
 impl PongOptions {
+    /// The default options with colors from the Okabe-Ito palette, which stay distinguishable
+    /// for the common types of color blindness: an orange and a sky blue player on a black
+    /// background with a white ball and score.
+    pub fn accessible() -> Self {
+        let mut options = Self::default();
+        options.game.background = Background::Color(Color::BLACK);
+        options.player.colors = (Color::rgb_u8(230, 159, 0), Color::rgb_u8(86, 180, 233));
+        options.ball.color = Color::WHITE;
+        options
+    }
+    /// The default options for solo rebound practice: only player 1's paddle is spawned and the
+    /// ball bounces off a wall on the right (see `GameOptions::single_player`).
+    pub fn single_player() -> Self {
+        let mut options = Self::default();
+        options.game.single_player = true;
+        options
+    }
     pub fn color_for(&self, player: &Player) -> Color {
         match player {
             Player::Player1 => self.player.colors.0,
             Player::Player2 => self.player.colors.1,
         }
     }
+    /// Whether the side of the player is replaced by a wall.
+    pub fn wall_for(&self, player: &Player) -> bool {
+        match player {
+            Player::Player1 => self.game.left_wall,
+            Player::Player2 => self.game.right_wall || self.game.single_player,
+        }
+    }
     pub fn up_for(&self, player: &Player) -> KeyCode {
         match player {
             Player::Player1 => self.player.player1_keys.0,
@@ -127,273 +631,3352 @@ impl PongOptions {
             Player::Player2 => self.player.player2_keys.1,
         }
     }
+    pub fn dash_for(&self, player: &Player) -> Option<KeyCode> {
+        match player {
+            Player::Player1 => self.player.dash_keys.0,
+            Player::Player2 => self.player.dash_keys.1,
+        }
+    }
+    /// The color of a players score, which is only used if there is a score display.
+    pub fn score_color_for(&self, player: &Player) -> Color {
+        match &self.score_display_options {
+            Some(score_options) if score_options.color_per_player => self.color_for(player),
+            Some(score_options) => score_options.font_color,
+            None => Color::WHITE,
+        }
+    }
+    /// The number shown in the score display for the given points, see `ScoreDisplayOptions::count_down`.
+    pub fn displayed_score(&self, points: u16) -> u16 {
+        match (&self.score_display_options, self.game.win_score) {
+            (Some(score_options), Some(win_score)) if score_options.count_down => win_score.saturating_sub(points),
+            _ => points,
+        }
+    }
+    pub fn center_for(&self, player: &Player) -> Option<KeyCode> {
+        match player {
+            Player::Player1 => self.player.center_keys.0,
+            Player::Player2 => self.player.center_keys.1,
+        }
+    }
+    pub fn controller_for(&self, player: &Player) -> Controller {
+        match player {
+            Player::Player1 => self.player.controllers.0,
+            Player::Player2 => self.player.controllers.1,
+        }
+    }
+    /// The serving player after the given number of points, if `GameOptions::serve_rotation` is set.
+    fn server_after(&self, points_played: u16) -> Option<Player> {
+        self.game.serve_rotation.map(|rotation| {
+            if rotation == 0 || (points_played / rotation).is_multiple_of(2) {
+                Player::Player1
+            } else {
+                Player::Player2
+            }
+        })
+    }
+    /// The velocity of a serve with `BallOptions::start_velocity`, towards the opponent of the
+    /// server if there is one.
+    fn serve_velocity(&self, server: Option<Player>) -> Vec2 {
+        let mut velocity = (self.ball.start_velocity)();
+        if let Some(server) = server {
+            velocity.x = match server {
+                Player::Player1 => velocity.x.abs(),
+                Player::Player2 => -velocity.x.abs(),
+            };
+        }
+        velocity
+    }
+}
+
+/// Labels of the plugin's systems, to order other systems relative to them.
+#[derive(SystemLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PongSystem {
+    /// Runs before the input is handled, e.g. to inject input into the `PlayerInput` resource or to
+    /// change the options. The plugin only adds an empty system with this label.
+    PreInput,
+    /// Handles the player input and the requests sent as events (spawning balls, power-ups, ...).
+    Input,
+    /// Moves the balls and checks for scored points, with the `PhysicsSchedule`.
+    Physics,
+    /// Reacts to the physics, e.g. updates the score display and sends the game's events.
+    PostPhysics,
+}
+
+/// Labels of systems which are only ordered relative to each other within the plugin.
+#[derive(SystemLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum InternalSystem {
+    /// Records the positions after the physics, which get interpolated afterwards.
+    RecordPositions,
+    /// Animates the score text, the win celebration overrides its font sizes afterwards.
+    ScoreAnimation,
+}
+
+/// Determines how often the physics systems (speeding up and moving the ball and checking for
+/// scored points) run. The timed speedup of the ball uses the same clock as the movement, so the
+/// difficulty ramp stays in sync with the ball.
+#[derive(Copy, Clone, Default)]
+pub enum PhysicsSchedule {
+    /// Once every frame, using the frame time as delta.
+    #[default]
+    EveryFrame,
+    /// With a fixed timestep (in seconds), independent of the frame rate. This makes the
+    /// physics deterministic, while the input still gets handled every frame.
+    FixedTimestep(f64),
+}
+
+type ScoreCallback = Box<dyn FnMut(Player, u16) + Send + Sync>;
+type BounceCallback = Box<dyn FnMut(&BounceEvent) + Send + Sync>;
+type BallSpawnHook = Box<dyn Fn(&mut EntityCommands) + Send + Sync>;
+type PaddleSpawnHook = Box<dyn Fn(Player, &mut EntityCommands) + Send + Sync>;
+
+/// The callbacks registered at the plugin, they are called by a system reading the events. The
+/// spawn hooks are called directly by the systems spawning the balls and paddles.
+#[derive(Default)]
+struct PongCallbacks {
+    on_score: Vec<ScoreCallback>,
+    on_bounce: Vec<BounceCallback>,
+    ball_components: Vec<BallSpawnHook>,
+    paddle_components: Vec<PaddleSpawnHook>,
+}
+
+impl PongCallbacks {
+    fn init_ball(&self, ball: &mut EntityCommands) {
+        for hook in self.ball_components.iter() {
+            hook(ball);
+        }
+    }
+    fn init_paddle(&self, player: Player, paddle: &mut EntityCommands) {
+        for hook in self.paddle_components.iter() {
+            hook(player, paddle);
+        }
+    }
 }
 
-pub struct PongPlugin;
+#[derive(Default)]
+pub struct PongPlugin {
+    physics_schedule: PhysicsSchedule,
+    without_default_game: bool,
+    // The plugin only gets borrowed while building, so the callbacks are taken out of the mutex.
+    callbacks: Mutex<PongCallbacks>,
+}
+
+impl PongPlugin {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets the schedule of the physics systems, `PhysicsSchedule::EveryFrame` by default.
+    pub fn with_physics_schedule(mut self, schedule: PhysicsSchedule) -> Self {
+        self.physics_schedule = schedule;
+        self
+    }
+    /// Doesn't spawn a game at startup, the games are spawned with `PongGameBundle`s instead (e.g.
+    /// several games side by side).
+    pub fn without_default_game(mut self) -> Self {
+        self.without_default_game = true;
+        self
+    }
+    /// Registers a callback, which gets called with the player and their new score every time a
+    /// player scores a point. This is an alternative to reading the `ScoredPointEvent`.
+    pub fn on_score(mut self, callback: impl FnMut(Player, u16) + Send + Sync + 'static) -> Self {
+        self.callbacks.get_mut().unwrap().on_score.push(Box::new(callback));
+        self
+    }
+    /// Registers a callback, which gets called every time a ball bounces off a paddle or a wall.
+    /// This is an alternative to reading the `BounceEvent`.
+    pub fn on_bounce(mut self, callback: impl FnMut(&BounceEvent) + Send + Sync + 'static) -> Self {
+        self.callbacks.get_mut().unwrap().on_bounce.push(Box::new(callback));
+        self
+    }
+    /// Registers a hook, which gets called for every spawned ball (including balls spawned later,
+    /// e.g. by a `SpawnBallEvent`), to insert custom components.
+    pub fn with_ball_components(mut self, hook: impl Fn(&mut EntityCommands) + Send + Sync + 'static) -> Self {
+        self.callbacks.get_mut().unwrap().ball_components.push(Box::new(hook));
+        self
+    }
+    /// Registers a hook, which gets called with the player for every spawned paddle, to insert
+    /// custom components.
+    pub fn with_paddle_components(
+        mut self,
+        hook: impl Fn(Player, &mut EntityCommands) + Send + Sync + 'static,
+    ) -> Self {
+        self.callbacks.get_mut().unwrap().paddle_components.push(Box::new(hook));
+        self
+    }
+}
 
 impl Plugin for PongPlugin {
     fn build(&self, app: &mut App) {
+        // User systems labelled with `PongSystem::PreInput` run before the input is handled.
+        let input = SystemSet::new()
+            .label(PongSystem::Input)
+            .after(PongSystem::PreInput)
+            .with_system(handle_player_input)
+            .with_system(invert_controls)
+            .with_system(expire_inverted_controls)
+            .with_system(boost_speed)
+            .with_system(expire_speed_boosts)
+            .with_system(wait_for_first_serve)
+            .with_system(spawn_requested_balls)
+            .with_system(set_ball_velocity)
+            .with_system(reset_requested_balls)
+            .with_system(toggle_score_display)
+            .with_system(resize_game)
+            .with_system(reset_game)
+            .with_system(pause_on_focus_loss);
+
+        // The speedup, the escalation and the match timer are part of the physics, so they use the
+        // same clock as the ball movement.
+        let physics = SystemSet::new()
+            .with_system(tick_match_elapsed.before(PongSystem::Physics))
+            .with_system(speedup_ball.before(PongSystem::Physics))
+            .with_system(restore_physics_positions.before(PongSystem::Physics))
+            .with_system(apply_ball_velocity.label(PongSystem::Physics).after(PongSystem::Input))
+            .with_system(check_point_scored.label(PongSystem::Physics).after(PongSystem::Input))
+            .with_system(escalate_rally.after(PongSystem::Physics))
+            .with_system(tick_match_timer.after(PongSystem::Physics))
+            .with_system(record_physics_positions.label(InternalSystem::RecordPositions).after(PongSystem::Physics));
+        let (physics, step) = match self.physics_schedule {
+            PhysicsSchedule::EveryFrame => (physics, None),
+            PhysicsSchedule::FixedTimestep(step) => (
+                physics.with_run_criteria(FixedTimestep::step(step).with_label(PHYSICS_TIMESTEP)),
+                Some(step as f32),
+            ),
+        };
+
+        let callbacks = std::mem::take(&mut *self.callbacks.lock().unwrap());
+
         app.add_event::<ScoredPointEvent>()
-            .add_startup_system(setup_pong)
-            .add_system(handle_player_input.label("a"))
-            .add_system(speedup_ball.label("a"))
-            .add_system(apply_ball_velocity.label("b").after("a"))
-            .add_system(check_point_scored.label("b").after("a"))
-            .add_system(update_score_text.label("c").after("b"));
+            .add_event::<GoalEvent>()
+            .add_event::<ScoreMilestoneEvent>()
+            .add_event::<MatchPointEvent>()
+            .add_event::<GameOverEvent>()
+            .add_event::<StreakEvent>()
+            .add_event::<PerfectHitEvent>()
+            .add_event::<DrawEvent>()
+            .add_event::<GoldenGoalEvent>()
+            .add_event::<BounceEvent>()
+            .add_event::<DashEvent>()
+            .add_event::<CenterPaddleEvent>()
+            .add_event::<InvertControlsEvent>()
+            .add_event::<ControlsInvertedEvent>()
+            .add_event::<ControlsRestoredEvent>()
+            .add_event::<SpeedBoostEvent>()
+            .add_event::<SpeedBoostAppliedEvent>()
+            .add_event::<SpeedBoostExpiredEvent>()
+            .add_event::<SpawnBallEvent>()
+            .add_event::<BallSpawnRejectedEvent>()
+            .add_event::<SetBallVelocityEvent>()
+            .add_event::<ResetBallEvent>()
+            .add_event::<SpawnScoreDisplayEvent>()
+            .add_event::<DespawnScoreDisplayEvent>()
+            .add_event::<ResetGameEvent>()
+            .insert_resource(callbacks)
+            .init_resource::<FreezeBall>()
+            .init_resource::<FreezeSpeedup>()
+            .init_resource::<PongPaused>()
+            .init_resource::<PlayerInput>()
+            .insert_resource(PhysicsStep(step))
+            .add_system_to_stage(CoreStage::PreUpdate, setup_games)
+            .add_system(pre_input.label(PongSystem::PreInput))
+            .add_system(sync_default_options.after(PongSystem::PreInput).before(PongSystem::Input))
+            .add_system(restore_paddle_positions.before(PongSystem::Input))
+            .add_system_set(input)
+            .add_system_set(physics)
+            .add_system(update_score_text.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(update_score_images.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(update_ball_speed_text.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(animate_score_text.label(PongSystem::PostPhysics).label(InternalSystem::ScoreAnimation).after(PongSystem::Physics))
+            .add_system(celebrate_win.label(PongSystem::PostPhysics).after(InternalSystem::ScoreAnimation))
+            .add_system(sync_pong_state.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(sync_paddle_positions.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(run_callbacks.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(spawn_goal_particles.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(flash_goal.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(handle_game_over.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(track_streaks.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(collect_stats.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(tick_time_since_goal.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(interpolate_positions.label(PongSystem::PostPhysics).after(InternalSystem::RecordPositions))
+            .add_system(spawn_ball_shadows.label(PongSystem::PostPhysics).after(PongSystem::Physics))
+            .add_system(update_ball_shadows.after(PongSystem::PostPhysics))
+            .add_system(round_corners.after(PongSystem::PostPhysics))
+            .add_system(update_goal_particles)
+            .add_system(update_impact_marks);
+
+        if !self.without_default_game {
+            app.add_startup_system(spawn_default_game);
+        }
+        #[cfg(feature = "audio")]
+        app.add_system(play_paddle_sounds.label(PongSystem::PostPhysics).after(PongSystem::Physics));
     }
 }
 
+/// Sounds played by the plugin, which are used if this is inserted as resource (requires the
+/// `audio` feature).
+#[cfg(feature = "audio")]
+#[derive(Clone, Default)]
+pub struct SoundOptions {
+    /// Sounds played when the ball hits the paddle of player 1 (paddle_sounds.0) or player 2 (paddle_sounds.1),
+    /// so the players can hear who hit the ball.
+    pub paddle_sounds: (Option<Handle<AudioSource>>, Option<Handle<AudioSource>>),
+}
+
+/// The root of a game, its paddles, balls and score display are its children. Every game has its
+/// own options and state, so several games can run side by side, see `PongGameBundle`.
 #[derive(Component)]
 pub struct PongGame;
 
+/// Marks the game spawned by the plugin, which follows the changes of the `PongOptions` resource.
 #[derive(Component)]
-pub struct Ball;
-
-impl Ball {
-    fn start_position(options: &PongOptions) -> Vec3 {
-        Vec3::new(0., 0., options.game.position.z + 1.)
-    }
-}
+struct DefaultGame;
 
-#[derive(Component)]
-pub struct Velocity(Vec2);
+pub use game_bundle::PongGameBundle;
 
-struct BallSpeedupTimer(Timer);
+// Like for the `BallBundle`, the derive forgets the moved out components.
+#[allow(clippy::forget_non_drop)]
+mod game_bundle {
+    use super::*;
 
-#[derive(Component, Copy, Clone, PartialEq, Eq)]
-pub enum Player {
-    Player1,
-    Player2,
-}
+    /// A game with the given options, e.g. to show several games side by side (with different
+    /// `GameOptions::position`s). The plugin spawns the paddles, the ball and the score display as
+    /// children and inserts the state of the game (like the `BallSpeedupTimer`) in the next
+    /// `CoreStage::PreUpdate`:
+    /// ```ignore
+    /// commands.spawn_bundle(PongGameBundle::new(options));
+    /// ```
+    #[derive(Bundle)]
+    pub struct PongGameBundle {
+        game: PongGame,
+        options: PongOptions,
+    }
 
-impl Player {
-    fn start_position(&self, options: &PongOptions) -> Vec3 {
-        let x = options.game.size.x / 2. - options.player.size.x;
-        let z = options.game.position.z + 1.;
-        match self {
-            Player::Player1 => Vec3::new(-x, 0., z),
-            Player::Player2 => Vec3::new(x, 0., z),
+    impl PongGameBundle {
+        pub fn new(options: PongOptions) -> Self {
+            Self { game: PongGame, options }
         }
     }
 }
 
-#[derive(Component, Clone, Copy)]
-pub struct Score(u16);
+#[derive(Component)]
+pub struct Ball;
+
+impl Ball {
+    /// The `BallOptions::start_position`, clamped so the ball starts fully inside the game.
+    fn start_position(options: &PongOptions) -> Vec3 {
+        let start = options.ball.start_position;
+        let max_x = (options.game.size.x - options.ball.collision_size().x).max(0.) / 2.;
+        Vec3::new(start.x.clamp(-max_x, max_x), Ball::clamp_y(options, start.y), options.game.position.z + 1.)
+    }
+    /// Clamps the vertical position, so the ball is fully inside the game. If the ball is larger
+    /// than the game, it gets centered.
+    fn clamp_y(options: &PongOptions, y: f32) -> f32 {
+        let max_y = (options.game.size.y - options.ball.collision_size().y).max(0.) / 2.;
+        y.clamp(-max_y, max_y)
+    }
+}
 
 #[derive(Component)]
-pub struct ScoreDisplayText;
+pub struct Velocity(Vec2);
 
-pub struct ScoredPointEvent(Player, Score);
+pub use ball_bundle::BallBundle;
 
-pub type IsBall = (With<Ball>, Without<Player>);
-pub type IsPlayer = (With<Player>, Without<Ball>);
+// The `Bundle` derive of bevy 0.6 forgets the components after moving them out of the bundle.
+#[allow(clippy::forget_non_drop)]
+mod ball_bundle {
+    use super::*;
 
-fn setup_pong(mut commands: Commands, asset_server: Res<AssetServer>, pong_options: Option<Res<PongOptions>>) {
-    let options = match pong_options {
-        Some(opt) => *opt,
-        None => {
-            commands.insert_resource(PongOptions::default());
-            PongOptions::default()
-        }
-    };
+    /// All components of a ball, exactly like the plugin spawns them. To take part in the game, the
+    /// ball must be spawned as child of the `PongGame` entity:
+    /// ```ignore
+    /// commands.entity(game).with_children(|parent| {
+    ///     parent.spawn_bundle(BallBundle::new(&options));
+    /// });
+    /// ```
+    #[derive(Bundle)]
+    pub struct BallBundle {
+        ball: Ball,
+        velocity: Velocity,
+        paddle_bounce_cooldown: PaddleBounceCooldown,
+        wall_bounce_cooldown: WallBounceCooldown,
+        last_touched_by: LastTouchedBy,
+        serve_ramp: ServeRamp,
+        size: BallSize,
+        interpolation: Interpolation,
+        #[bundle]
+        sprite: SpriteBundle,
+    }
 
-    let entity = commands.spawn()
-        .insert(PongGame)
-        .insert_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: options.game.background,
-                custom_size: Some(options.game.size),
-                ..Default::default()
-            },
-            transform: Transform::from_translation(options.game.position),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            for player in [Player::Player1, Player::Player2].iter() {
-                parent.spawn()
-                    .insert(*player)
-                    .insert_bundle(SpriteBundle {
-                        sprite: Sprite {
-                            color: options.color_for(player),
-                            custom_size: Some(options.player.size),
-                            ..Default::default()
-                        },
-                        transform: Transform::from_translation(player.start_position(&options)),
-                        ..Default::default()
-                    })
-                    .insert(Score(0))
-                    .insert(Velocity(Vec2::default()));
-            }
-            parent.spawn().insert(Ball)
-                .insert_bundle(SpriteBundle {
+    impl BallBundle {
+        /// A ball at its start position with a velocity from `BallOptions::start_velocity`.
+        pub fn new(options: &PongOptions) -> Self {
+            Self {
+                ball: Ball,
+                velocity: Velocity((options.ball.start_velocity)()),
+                paddle_bounce_cooldown: PaddleBounceCooldown(0),
+                wall_bounce_cooldown: WallBounceCooldown(0.),
+                last_touched_by: LastTouchedBy(None),
+                serve_ramp: ServeRamp::new(options),
+                interpolation: Interpolation::default(),
+                size: BallSize(options.ball.size),
+                sprite: SpriteBundle {
                     sprite: Sprite {
                         color: options.ball.color,
                         custom_size: Some(options.ball.size),
                         ..Default::default()
                     },
-                    transform: Transform::from_translation(Ball::start_position(&options)),
-                    ..Default::default()
-                })
-                .insert(Velocity((options.ball.start_velocity)()));
-        }).id();
-    
-    if options.score_display_options.is_some() {
-        let score_options = options.score_display_options.unwrap();
-        let text_style = TextStyle {
-                        font: asset_server.load(score_options.font_path),
-                        font_size: score_options.font_size,
-                        color: score_options.font_color,
-        };
-        let section = |s: &str| TextSection { value: s.into(), style: text_style.clone() };
-
-        commands.entity(entity).with_children(|parent| {
-            parent.spawn().insert(ScoreDisplayText)
-                .insert_bundle(Text2dBundle {
-                    text: Text {
-                        sections: vec![ section("0"), section(":"), section("0") ],
-                        alignment: TextAlignment {
-                            vertical: VerticalAlign::Center,
-                            horizontal: HorizontalAlign::Center,
-                        },
-                    },
-                    transform: Transform::from_translation(Vec3::new(
-                        0.,
-                        options.game.size.y / 2. - score_options.font_size * (2. / 3.),
-                        options.game.position.z + 1.
-                    )),
+                    transform: Transform::from_translation(Ball::start_position(options)),
                     ..Default::default()
-                });
-        });
+                },
+            }
+        }
+        /// Sets the position relative to the game's center.
+        pub fn with_position(mut self, position: Vec2) -> Self {
+            self.sprite.transform.translation.x = position.x;
+            self.sprite.transform.translation.y = position.y;
+            self
+        }
+        pub fn with_velocity(mut self, velocity: Vec2) -> Self {
+            self.velocity.0 = velocity;
+            self
+        }
     }
-
-    commands.insert_resource(BallSpeedupTimer(
-            Timer::from_seconds(options.ball.speedup_time, true)
-    ));
 }
 
-fn handle_player_input(
-    options: Res<PongOptions>,
-    time: Res<Time>,
-    key_input: Res<Input<KeyCode>>,
-    mut players: Query<(&Player, &mut Transform)>
-) {
-    let delta = time.delta_seconds();
-    let movement = options.player.speed * delta;
-    let hps = options.player.size.y / 2.;
-    let hgs = options.game.size.y / 2.;
+/// The timer of the periodic ball speedup, e.g. to show the time until the next speedup. Like the
+/// other state of a game, it is a component of the `PongGame` entity.
+#[derive(Component)]
+pub struct BallSpeedupTimer(Timer);
 
-    for (player, mut transform) in players.iter_mut() {
-        let y = &mut transform.translation.y;
-        if key_input.pressed(options.up_for(player)) && (*y + hps + movement) <= hgs {
-            *y += movement;
-        }
-        if key_input.pressed(options.down_for(player)) && (*y - hps - movement) >= -hgs {
-            *y -= movement;
-        }
+impl BallSpeedupTimer {
+    /// The time (in seconds) since the last speedup.
+    pub fn elapsed_secs(&self) -> f32 {
+        self.0.elapsed_secs()
+    }
+    /// The time (in seconds) until the next speedup.
+    pub fn remaining_secs(&self) -> f32 {
+        self.0.duration().as_secs_f32() - self.0.elapsed_secs()
+    }
+    /// Restarts the period, so the next speedup happens after the full `BallOptions::speedup_time`.
+    pub fn reset(&mut self) {
+        self.0.reset();
     }
 }
 
-fn speedup_ball(
-    mut ball_timer: ResMut<BallSpeedupTimer>,
-    time: Res<Time>,
-    options: Res<PongOptions>,
-    mut ball_velocities: Query<&mut Velocity, IsBall>,
-) {
-    if !ball_timer.0.tick(time.delta()).just_finished() {
-        return;
+/// The time (in seconds) the current game has been running, without the time it was paused or over.
+#[derive(Component, Default)]
+pub struct MatchElapsed(f32);
+
+impl MatchElapsed {
+    pub fn elapsed_secs(&self) -> f32 {
+        self.0
     }
+}
+
+/// The time (in seconds) since the last goal or the start of the game, e.g. to drive idle
+/// animations. It doesn't advance while the game is paused.
+#[derive(Component, Default)]
+pub struct TimeSinceGoal(f32);
 
-    for mut vel in ball_velocities.iter_mut() {
-        vel.0 *= options.ball.speedup_factor;
+impl TimeSinceGoal {
+    pub fn secs(&self) -> f32 {
+        self.0
     }
 }
 
-fn apply_ball_velocity(
-    time: Res<Time>,
-    options: Res<PongOptions>,
-    mut balls: Query<(&mut Transform, &mut Velocity), IsBall>,
-    players: Query<&Transform, IsPlayer>,
-) {
-    let delta = time.delta_seconds();
+/// While true, the balls of all games neither move nor get faster, but the players can still move
+/// their paddles.
+#[derive(Default)]
+pub struct FreezeBall(pub bool);
 
-    let hgs = options.game.size.y / 2.;
-    let hbs = options.ball.size.y / 2.;
-    for (mut trans, mut vel) in balls.iter_mut() {
-        trans.translation.x += vel.0.x * delta;
-        trans.translation.y += vel.0.y * delta;
-
-        for p_trans in players.iter() {
-            if let Some(col) = collide(
-                p_trans.translation, options.player.size,
-                trans.translation, options.ball.size
-            ) {
-                match col {
-                    Collision::Left | Collision::Right => vel.0.x *= -1.,
-                    Collision::Top | Collision::Bottom => vel.0.y *= -1.,
-                }
-            }
-        }
+/// While true, the balls of all games keep moving with their current speed, but don't get faster.
+#[derive(Default)]
+pub struct FreezeSpeedup(pub bool);
 
-        if trans.translation.y + hbs >= hgs {           // Ball hits top
-            vel.0.y *= -1.;
-            trans.translation.y = hgs - hbs;
-        } else if trans.translation.y - hbs <= -hgs {   // Ball hits bottom
-            vel.0.y *= -1.;
-            trans.translation.y = -hgs + hbs;
-        }
+/// The timer of the `GameOptions::time_limit`, which is only inserted if the game has a time limit,
+/// e.g. to show a countdown.
+#[derive(Component)]
+pub struct MatchTimer(Timer);
+
+impl MatchTimer {
+    /// The time (in seconds) until the game is over.
+    pub fn remaining_secs(&self) -> f32 {
+        self.0.duration().as_secs_f32() - self.0.elapsed_secs()
     }
 }
 
-fn check_point_scored(
-    options: Res<PongOptions>,
-    mut event_writer: EventWriter<ScoredPointEvent>,
-    mut balls: Query<(&mut Transform, &mut Velocity), IsBall>,
-    mut players: Query<(&Player, &mut Transform, &mut Score), IsPlayer>
-) {
-    let max_x = options.game.size.x / 2.;
-    let min_x = -max_x;
-    let hbsx = options.ball.size.x / 2.;
-
-    let reset_ball = |mut t: &mut Transform, mut v: &mut Velocity| {
-        t.translation = Vec3::new(0., 0., 1.);
-        v.0 = (options.ball.start_velocity)();
-    };
-    let mut reset_player_and_send_event = |scoring_player: Player| {
-        for (player, mut p_trans, mut score) in players.iter_mut() {
-            if *player == scoring_player {
-                score.0 += 1;
-                event_writer.send(ScoredPointEvent(*player, *score));
-            }
-            p_trans.translation.y = 0.;
-        }
-    };
+/// Whether the game is in overtime, because the `GameOptions::time_limit` was reached with tied
+/// scores and `GameOptions::sudden_death` is set. The next point wins the game.
+#[derive(Component, Default)]
+pub struct Overtime(bool);
 
-    for (mut b_trans, mut vel) in balls.iter_mut() {
-        if b_trans.translation.x - hbsx <= min_x {
-            reset_ball(&mut b_trans, &mut vel);
-            reset_player_and_send_event(Player::Player2);
-        } else if b_trans.translation.x + hbsx >= max_x {
-            reset_ball(&mut b_trans, &mut vel);
-            reset_player_and_send_event(Player::Player1);
-        }
+impl Overtime {
+    pub fn is_active(&self) -> bool {
+        self.0
     }
 }
 
-fn update_score_text(
-    options: Res<PongOptions>,
-    mut event_reader: EventReader<ScoredPointEvent>,
-    mut score_text: Query<&mut Text, With<ScoreDisplayText>>,
-) {
-    if options.score_display_options.is_none() {
-        return;
-    }
+/// The movement directions of the players controlled by `Controller::External` (0 is for player 1;
+/// 1 is for player 2), from -1 (down) to 1 (up). The resource is used by all games, unless a
+/// `PongGame` entity has its own `PlayerInput` component.
+#[derive(Component, Default)]
+pub struct PlayerInput(pub f32, pub f32);
 
-    for ScoredPointEvent(player, Score(points)) in event_reader.iter() {
-        for mut text in score_text.iter_mut() {
-            match player {
-                Player::Player1 => text.sections[0].value = format!("{}", points),
-                Player::Player2 => text.sections[2].value = format!("{}", points),
-            }
+impl PlayerInput {
+    pub fn direction_for(&self, player: &Player) -> f32 {
+        match player {
+            Player::Player1 => self.0,
+            Player::Player2 => self.1,
+        }
+    }
+    pub fn set_direction(&mut self, player: &Player, direction: f32) {
+        match player {
+            Player::Player1 => self.0 = direction,
+            Player::Player2 => self.1 = direction,
         }
     }
-}
\ No newline at end of file
+}
+
+/// While true, all games are paused: neither the balls nor the paddles move.
+#[derive(Default)]
+pub struct PongPaused(pub bool);
+
+/// The player who scored the last points and how many of them in a row.
+#[derive(Component, Default)]
+struct Streak {
+    player: Option<Player>,
+    count: u16,
+}
+
+/// Whether the game is over and the timer of `AfterGameOver::AutoRestart`.
+#[derive(Component, Default)]
+struct GameOverState {
+    over: bool,
+    restart_timer: Option<Timer>,
+}
+
+/// True until the first serve, if `GameOptions::wait_for_ready` is set.
+#[derive(Component)]
+struct AwaitingFirstServe(bool);
+
+/// The player who last hit the ball with their paddle, `None` if nobody touched it since the last serve.
+#[derive(Component, Copy, Clone)]
+pub struct LastTouchedBy(pub Option<Player>);
+
+/// The current size of a ball, which differs from `BallOptions::size` if the ball grows.
+#[derive(Component)]
+struct BallSize(Vec2);
+
+/// How a ball gets served after it was reset.
+#[derive(Copy, Clone)]
+enum Serve {
+    /// With `BallOptions::start_velocity`, towards the opponent of the serving player if there is one.
+    StartVelocity(Option<Player>),
+    /// Not at all, because the game is over.
+    Stopped,
+}
+
+/// Marks a ball which scored a goal and gets reset once the timer (see `GameOptions::goal_delay`)
+/// finishes.
+#[derive(Component)]
+struct PendingReset {
+    timer: Timer,
+    serve: Serve,
+    scorer: Player,
+}
+
+/// Fraction of its speed the ball starts with, if `BallOptions::serve_ramp` is set.
+const SERVE_RAMP_START: f32 = 0.2;
+
+/// Timer of the speed ramp after a serve, `None` if `BallOptions::serve_ramp` is not set.
+#[derive(Component)]
+struct ServeRamp(Option<Timer>);
+
+impl ServeRamp {
+    fn new(options: &PongOptions) -> Self {
+        ServeRamp(options.ball.serve_ramp.map(|duration| Timer::from_seconds(duration, false)))
+    }
+    /// Ticks the timer and returns the factor for the current speed of the ball.
+    fn tick(&mut self, delta: f32) -> f32 {
+        if let Some(timer) = &mut self.0 {
+            if !timer.tick(Duration::from_secs_f32(delta)).finished() {
+                return SERVE_RAMP_START + (1. - SERVE_RAMP_START) * timer.percent();
+            }
+        }
+        1.
+    }
+    fn reset(&mut self) {
+        if let Some(timer) = &mut self.0 {
+            timer.reset();
+        }
+    }
+}
+
+/// Number of physics updates after a paddle bounce, in which the ball can not bounce off a paddle again.
+const PADDLE_BOUNCE_COOLDOWN: u8 = 3;
+
+/// Remaining physics updates until the ball can bounce off a paddle again; prevents a fast
+/// ball from being flipped twice by the same paddle and passing through it.
+#[derive(Component)]
+struct PaddleBounceCooldown(u8);
+
+/// Remaining time (in seconds) until the ball can bounce off the top or bottom wall again, see
+/// `BallOptions::wall_bounce_interval`.
+#[derive(Component)]
+struct WallBounceCooldown(f32);
+
+/// The fixed timestep of the physics systems, `None` if they run every frame.
+struct PhysicsStep(Option<f32>);
+
+/// Label of the fixed timestep of the physics systems, used to get the progress towards the next step.
+const PHYSICS_TIMESTEP: &str = "pong_physics";
+
+/// The positions of a ball or paddle after the last two physics steps and the position it was
+/// rendered at, if `GameOptions::interpolate` is set.
+#[derive(Component, Default)]
+struct Interpolation {
+    previous: Vec3,
+    current: Vec3,
+    rendered: Vec3,
+    /// The actual position of a paddle, which (unlike a ball) is moved by the input every frame.
+    actual: Vec3,
+}
+
+impl Interpolation {
+    /// Moves the last physics positions to the given position, so a ball or paddle which jumped
+    /// there (e.g. a reset after a goal) is rendered there right away instead of on its way from
+    /// the old position.
+    fn snap(&mut self, position: Vec3) {
+        self.previous = position;
+        self.current = position;
+    }
+}
+
+impl PhysicsStep {
+    fn delta(&self, time: &Time) -> f32 {
+        self.0.unwrap_or_else(|| time.delta_seconds())
+    }
+}
+
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Player {
+    Player1,
+    Player2,
+}
+
+impl Player {
+    pub fn opponent(&self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        }
+    }
+    fn start_position(&self, options: &PongOptions) -> Vec3 {
+        let x = options.game.size.x / 2. - options.player.size.x;
+        let z = options.game.position.z + 1.;
+        match self {
+            Player::Player1 => Vec3::new(-x, options.player.start_offsets.0, z),
+            Player::Player2 => Vec3::new(x, options.player.start_offsets.1, z),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct Score(u16);
+
+/// Marks a player whose up and down keys are swapped until the timer finishes.
+#[derive(Component)]
+pub struct InvertedControls(Timer);
+
+/// Multiplies the speed of a players paddle until the timer finishes.
+#[derive(Component)]
+pub struct SpeedBoost {
+    multiplier: f32,
+    timer: Timer,
+}
+
+const GOAL_PARTICLE_COUNT: usize = 12;
+const GOAL_PARTICLE_SIZE: f32 = 4.;
+const GOAL_PARTICLE_SPEED: f32 = 120.;
+/// Time (in seconds) until a goal particle is faded out and despawned.
+const GOAL_PARTICLE_LIFETIME: f32 = 0.6;
+const IMPACT_MARK_SIZE: f32 = 4.;
+/// Time (in seconds) until an impact mark is faded out and despawned.
+const IMPACT_MARK_LIFETIME: f32 = 0.4;
+
+/// A mark on a paddle where the ball hit it, if `GameOptions::impact_marks` is set.
+#[derive(Component)]
+struct ImpactMark(Timer);
+
+/// The rounded mesh rendered instead of the sprite of a ball or paddle, if its corner radius
+/// (`BallOptions::corner_radius` or `PlayerOptions::corner_radius`) is greater than 0. Holds
+/// the size and radius the mesh was generated for.
+#[derive(Component)]
+struct RoundedCorners {
+    size: Vec2,
+    radius: f32,
+}
+
+/// Number of segments of each rounded corner.
+const CORNER_SEGMENTS: usize = 8;
+
+/// The shadow of a ball, if `GameOptions::ball_shadow` is set.
+#[derive(Component)]
+struct BallShadow(Entity);
+
+/// Time (in seconds) the background flashes after a goal, if `GameOptions::goal_flash` is set.
+const GOAL_FLASH_DURATION: f32 = 0.15;
+
+#[derive(Component)]
+struct GoalParticle {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+/// Duration (in seconds) of a dash.
+const DASH_DURATION: f32 = 0.15;
+
+/// The remaining time of the current dash and of the cooldown until the next dash (in seconds).
+#[derive(Component, Default)]
+struct Dash {
+    remaining: f32,
+    cooldown: f32,
+}
+
+/// The remaining time (in seconds) until a held key moves the paddle by another step, if the
+/// `MovementMode` is `Stepped`.
+#[derive(Component, Default)]
+struct StepCooldown(f32);
+
+pub use player_bundle::PlayerBundle;
+
+// Like for the `BallBundle`, the derive forgets the moved out components.
+#[allow(clippy::forget_non_drop)]
+mod player_bundle {
+    use super::*;
+
+    /// All components of a players paddle, exactly like the plugin spawns them. Like the `BallBundle`
+    /// it must be spawned as child of the `PongGame` entity.
+    #[derive(Bundle)]
+    pub struct PlayerBundle {
+        player: Player,
+        score: Score,
+        velocity: Velocity,
+        dash: Dash,
+        step_cooldown: StepCooldown,
+        interpolation: Interpolation,
+        #[bundle]
+        sprite: SpriteBundle,
+    }
+
+    impl PlayerBundle {
+        /// A paddle at the start position of the player with a score of 0.
+        pub fn new(player: Player, options: &PongOptions) -> Self {
+            Self {
+                player,
+                score: Score(0),
+                velocity: Velocity(Vec2::default()),
+                dash: Dash::default(),
+                step_cooldown: StepCooldown::default(),
+                interpolation: Interpolation::default(),
+                sprite: SpriteBundle {
+                    sprite: Sprite {
+                        color: options.color_for(&player),
+                        custom_size: Some(options.player.size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(player.start_position(options)),
+                    ..Default::default()
+                },
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ScoreDisplayText;
+
+/// One side of the frame drawn if `GameOptions::border` is set, the index of `BorderOptions::sides`.
+#[derive(Component)]
+struct BorderSide(usize);
+
+/// Time (in seconds) a score text shrinks back to its size after a point, if
+/// `ScoreDisplayOptions::score_animation` is set.
+const SCORE_ANIMATION_DURATION: f32 = 0.3;
+/// The factor by which a score text grows when the score changes.
+const SCORE_ANIMATION_SCALE: f32 = 1.5;
+/// Bounces per second of the winners score, if `ScoreDisplayOptions::win_celebration` is set.
+const WIN_CELEBRATION_FREQUENCY: f32 = 1.5;
+/// The alpha of the losers score while the game is over.
+const WIN_CELEBRATION_DIM: f32 = 0.3;
+
+/// The text showing the speed of the fastest ball, if `ScoreDisplayOptions::show_ball_speed` is set.
+#[derive(Component)]
+pub struct BallSpeedText;
+
+/// A single digit of a players score, if the score is displayed as images.
+#[derive(Component)]
+pub struct ScoreDigit(pub Player);
+
+/// The offset of a score digit from the center of the score display, which is kept when the game
+/// gets resized.
+#[derive(Component)]
+struct DigitOffset(Vec3);
+
+pub struct ScoredPointEvent(Player, Score, Entity);
+
+impl ScoredPointEvent {
+    /// The `PongGame` entity of the game, in which the point was scored.
+    pub fn game(&self) -> Entity {
+        self.2
+    }
+}
+
+/// Snapshot of a running game, which can be used to save and restore it (serializable with the
+/// `serialize` feature). See `capture_snapshot` and `apply_snapshot`.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PongSnapshot {
+    pub scores: (u16, u16),
+    /// Positions of the paddles relative to the game's center.
+    pub paddles: (Vec3, Vec3),
+    pub balls: Vec<BallState>,
+    /// The elapsed time (in seconds) of the ball speedup timer.
+    pub speedup_elapsed: f32,
+}
+
+/// Whether a paddle moving with the given speed can reach the target y-position from its current
+/// y-position within `dt` seconds, e.g. for AI players or aim assistance.
+pub fn paddle_can_reach(current_y: f32, target_y: f32, speed: f32, dt: f32) -> bool {
+    (target_y - current_y).abs() <= speed.abs() * dt
+}
+
+/// Converts a position relative to the game's center into the range [-1, 1] (-1 is the left or
+/// bottom edge and 1 the right or top edge).
+pub fn normalized_pos(options: &PongOptions, position: Vec3) -> Vec2 {
+    position.truncate() / (options.game.size / 2.)
+}
+
+/// Converts a position relative to the game's center (like the positions of the balls and
+/// paddles) into a world position, taking `GameOptions::position` and the orientation into account.
+pub fn court_to_world(options: &PongOptions, local: Vec2) -> Vec3 {
+    options.game.position + options.game.orientation.rotation() * local.extend(0.)
+}
+
+/// Converts a world position into a position relative to the game's center, the inverse of
+/// `court_to_world`.
+pub fn world_to_court(options: &PongOptions, world: Vec3) -> Vec2 {
+    (options.game.orientation.rotation().inverse() * (world - options.game.position)).truncate()
+}
+
+/// The player who is ahead (scores.0 is for player 1; scores.1 is for player 2), `None` on a tie.
+pub fn leader(scores: (u16, u16)) -> Option<Player> {
+    match scores.0.cmp(&scores.1) {
+        std::cmp::Ordering::Greater => Some(Player::Player1),
+        std::cmp::Ordering::Less => Some(Player::Player2),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// The player who is currently ahead (see `leader`), using the scores of the paddles.
+pub fn current_leader(players: &Query<(&Player, &Score), IsPlayer>) -> Option<Player> {
+    let mut scores = (0, 0);
+    for (player, score) in players.iter() {
+        match player {
+            Player::Player1 => scores.0 = score.0,
+            Player::Player2 => scores.1 = score.0,
+        }
+    }
+    leader(scores)
+}
+
+/// The entity, position and velocity of every ball, e.g. for HUDs or effects with multiple
+/// balls. Allocates a new `Vec` on every call.
+pub fn active_balls(balls: &Query<(Entity, &Transform, &Velocity), IsBall>) -> Vec<(Entity, Vec3, Vec2)> {
+    balls.iter()
+        .map(|(ball, trans, vel)| (ball, trans.translation, vel.0))
+        .collect()
+}
+
+/// The normalized positions (see `normalized_pos`) of all balls, e.g. for minimaps or audio panning.
+pub fn ball_normalized_pos(options: &PongOptions, balls: &Query<&Transform, IsBall>) -> Vec<Vec2> {
+    balls.iter().map(|trans| normalized_pos(options, trans.translation)).collect()
+}
+
+/// Captures the current state of the given game.
+pub fn capture_snapshot(world: &mut World, game: Entity) -> PongSnapshot {
+    let mut snapshot = PongSnapshot::default();
+
+    let mut balls = world.query_filtered::<(&Parent, &Transform, &Velocity), IsBall>();
+    snapshot.balls = balls.iter(world)
+        .filter(|(parent, _, _)| parent.0 == game)
+        .map(|(_, trans, vel)| BallState { position: trans.translation, velocity: vel.0 })
+        .collect();
+    let mut players = world.query_filtered::<(&Parent, &Player, &Transform, &Score), IsPlayer>();
+    for (_, player, trans, score) in players.iter(world).filter(|(parent, ..)| parent.0 == game) {
+        match player {
+            Player::Player1 => {
+                snapshot.scores.0 = score.0;
+                snapshot.paddles.0 = trans.translation;
+            }
+            Player::Player2 => {
+                snapshot.scores.1 = score.0;
+                snapshot.paddles.1 = trans.translation;
+            }
+        }
+    }
+    if let Some(timer) = world.get::<BallSpeedupTimer>(game) {
+        snapshot.speedup_elapsed = timer.0.elapsed_secs();
+    }
+    snapshot
+}
+
+/// Restores the state of the given game from a snapshot. Balls are spawned or despawned, so their
+/// number matches the snapshot; balls beyond `BallOptions::max_balls` are dropped.
+pub fn apply_snapshot(world: &mut World, game: Entity, snapshot: &PongSnapshot) {
+    let options = match world.get::<PongOptions>(game) {
+        Some(options) => options.clone(),
+        None => return,
+    };
+
+    let mut players = world.query_filtered::<(&Parent, &Player, &mut Transform, &mut Score), IsPlayer>();
+    for (parent, player, mut trans, mut score) in players.iter_mut(world) {
+        if parent.0 != game {
+            continue;
+        }
+        let (points, position) = match player {
+            Player::Player1 => (snapshot.scores.0, snapshot.paddles.0),
+            Player::Player2 => (snapshot.scores.1, snapshot.paddles.1),
+        };
+        score.0 = points;
+        trans.translation = position;
+    }
+    let mut score_texts = world.query_filtered::<(&Parent, &mut Text), With<ScoreDisplayText>>();
+    for (_, mut text) in score_texts.iter_mut(world).filter(|(parent, _)| parent.0 == game) {
+        text.sections[0].value = format!("{}", options.displayed_score(snapshot.scores.0));
+        text.sections[2].value = format!("{}", options.displayed_score(snapshot.scores.1));
+    }
+    if let Some(mut timer) = world.get_mut::<BallSpeedupTimer>(game) {
+        timer.0.set_elapsed(Duration::from_secs_f32(snapshot.speedup_elapsed));
+    }
+
+    // The existing balls are reused, surplus ones get despawned and missing ones spawned.
+    let mut balls = world.query_filtered::<(Entity, &Parent, &mut Transform, &mut Velocity), IsBall>();
+    let mut states = snapshot.balls.iter().take(options.ball.max_balls);
+    let mut surplus = Vec::new();
+    for (entity, _, mut trans, mut vel) in balls.iter_mut(world).filter(|(_, parent, ..)| parent.0 == game) {
+        match states.next() {
+            Some(state) => {
+                trans.translation = state.position;
+                vel.0 = state.velocity;
+            }
+            None => surplus.push(entity),
+        }
+    }
+    let missing: Vec<BallState> = states.copied().collect();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    let callbacks = world.get_resource::<PongCallbacks>();
+    for entity in surplus {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.entity(game).with_children(|parent| {
+        for state in missing {
+            let mut ball = parent.spawn_bundle(BallBundle::new(&options)
+                .with_position(state.position.truncate())
+                .with_velocity(state.velocity));
+            if let Some(callbacks) = &callbacks {
+                callbacks.init_ball(&mut ball);
+            }
+        }
+    });
+    queue.apply(world);
+}
+
+/// Gives a point to a player of the given game outside of the normal goal flow, e.g. for scripted
+/// game modes. This is the canonical way to score programmatically: the same events as for a goal
+/// are sent (except the `GoalEvent`), so the score display and the game over handling follow.
+/// Returns whether the point ended the game.
+pub fn award_point(world: &mut World, game: Entity, player: Player) -> bool {
+    let options = match world.get::<PongOptions>(game) {
+        Some(options) => options.clone(),
+        None => return false,
+    };
+    let golden_goal = world.get::<Overtime>(game).is_some_and(|overtime| overtime.0);
+
+    let mut state = SystemState::<(PointWriters, Query<(&Parent, &Player, &mut Score), IsPlayer>)>::new(world);
+    let (mut writers, mut players) = state.get_mut(world);
+    match players.iter_mut().find(|(parent, p, _)| parent.0 == game && **p == player) {
+        Some((_, _, mut score)) => apply_point(&options, game, player, &mut score, golden_goal, &mut writers),
+        None => false,
+    }
+}
+
+/// The events sent when a player gets a point, see `apply_point`.
+#[derive(SystemParam)]
+struct PointWriters<'w, 's> {
+    scored: EventWriter<'w, 's, ScoredPointEvent>,
+    milestone: EventWriter<'w, 's, ScoreMilestoneEvent>,
+    match_point: EventWriter<'w, 's, MatchPointEvent>,
+    game_over: EventWriter<'w, 's, GameOverEvent>,
+    golden_goal: EventWriter<'w, 's, GoldenGoalEvent>,
+}
+
+/// Gives a point to a player of the given game and sends the resulting events, for goals as well
+/// as for `award_point`. If `golden_goal` is set (the game is in overtime), the point wins the game.
+/// Returns whether the point ended the game.
+fn apply_point(
+    options: &PongOptions,
+    game: Entity,
+    player: Player,
+    score: &mut Score,
+    golden_goal: bool,
+    writers: &mut PointWriters,
+) -> bool {
+    score.0 += 1;
+    writers.scored.send(ScoredPointEvent(player, *score, game));
+    if let Some(milestone) = options.game.milestone {
+        if milestone > 0 && score.0.is_multiple_of(milestone) {
+            writers.milestone.send(ScoreMilestoneEvent { player, score: score.0, game });
+        }
+    }
+    if golden_goal {
+        writers.game_over.send(GameOverEvent { winner: player, game });
+        writers.golden_goal.send(GoldenGoalEvent { winner: player, game });
+        return true;
+    }
+    match options.game.win_score {
+        Some(win_score) if score.0 >= win_score => {
+            writers.game_over.send(GameOverEvent { winner: player, game });
+            true
+        }
+        Some(win_score) if score.0 + 1 == win_score => {
+            writers.match_point.send(MatchPointEvent { player, game });
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Request to spawn an additional ball, which is rejected if `BallOptions::max_balls` is reached.
+pub struct SpawnBallEvent {
+    /// Position relative to the game's center, the start position of the ball is used if `None`.
+    pub position: Option<Vec2>,
+    /// The velocity of the new ball, `BallOptions::start_velocity` is used if `None`.
+    pub velocity: Option<Vec2>,
+    /// The `PongGame` entity of the game to spawn the ball in, a ball is spawned in every game if `None`.
+    pub game: Option<Entity>,
+}
+
+/// Sent when a `SpawnBallEvent` was rejected, because `BallOptions::max_balls` is reached.
+pub struct BallSpawnRejectedEvent {
+    pub game: Entity,
+}
+
+/// Sent when a player presses their center key, see `PlayerOptions::center_keys`.
+pub struct CenterPaddleEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Overwrites the velocity of a ball (of all balls if `entity` is `None`), e.g. for trick shots.
+/// The velocity is limited to `BallOptions::min_speed` and `BallOptions::max_speed`.
+pub struct SetBallVelocityEvent {
+    pub entity: Option<Entity>,
+    pub velocity: Vec2,
+}
+
+/// Moves a ball (all balls if `entity` is `None`) back to its start position and serves it again
+/// with `BallOptions::start_velocity` (towards the opponent of the current server, see
+/// `GameOptions::serve_rotation`), without changing the scores or the paddles.
+pub struct ResetBallEvent {
+    pub entity: Option<Entity>,
+}
+
+/// Spawns the score display with the given options, replacing the current one.
+pub struct SpawnScoreDisplayEvent {
+    pub options: ScoreDisplayOptions,
+    /// The `PongGame` entity of the game, the score display of every game is replaced if `None`.
+    pub game: Option<Entity>,
+}
+
+/// Despawns the score display of a game (of every game if `None`).
+pub struct DespawnScoreDisplayEvent(pub Option<Entity>);
+
+/// Sent when a player starts a dash.
+pub struct DashEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Inverts the controls of a player for the given duration (in seconds), e.g. as effect of a
+/// power-up. Applying it again while the controls are inverted restarts the duration.
+pub struct InvertControlsEvent {
+    pub player: Player,
+    pub duration: f32,
+    /// The `PongGame` entity of the game, the player is affected in every game if `None`.
+    pub game: Option<Entity>,
+}
+
+/// Sent when the controls of a player got inverted.
+pub struct ControlsInvertedEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Sent when the inverted controls of a player expired.
+pub struct ControlsRestoredEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Multiplies the speed of a players paddle for the given duration (in seconds), e.g. as effect of
+/// a power-up. Applying it again while the speed is boosted replaces the boost.
+pub struct SpeedBoostEvent {
+    pub player: Player,
+    pub multiplier: f32,
+    pub duration: f32,
+    /// The `PongGame` entity of the game, the player is affected in every game if `None`.
+    pub game: Option<Entity>,
+}
+
+/// Sent when the speed of a player got boosted.
+pub struct SpeedBoostAppliedEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Sent when the speed boost of a player expired.
+pub struct SpeedBoostExpiredEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Sent when a player reaches a multiple of `GameOptions::milestone` points.
+pub struct ScoreMilestoneEvent {
+    pub player: Player,
+    pub score: u16,
+    pub game: Entity,
+}
+
+/// Sent when a player is one point away from `GameOptions::win_score`.
+pub struct MatchPointEvent {
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// Sent when a player reaches `GameOptions::win_score`.
+pub struct GameOverEvent {
+    pub winner: Player,
+    pub game: Entity,
+}
+
+/// Sent when the `GameOptions::time_limit` is reached with tied scores and without `sudden_death`.
+pub struct DrawEvent {
+    pub game: Entity,
+}
+
+/// Sent (additionally to the `GameOverEvent`) when a player wins the game with a point in the overtime.
+pub struct GoldenGoalEvent {
+    pub winner: Player,
+    pub game: Entity,
+}
+
+/// Sent for every point of a player who scored at least `GameOptions::streak_threshold` points in a row.
+pub struct StreakEvent {
+    pub player: Player,
+    pub streak: u16,
+    pub game: Entity,
+}
+
+/// Sent when a ball hits a paddle close to its center, see `PlayerOptions::perfect_hit`.
+pub struct PerfectHitEvent {
+    pub ball: Entity,
+    pub player: Player,
+    pub game: Entity,
+}
+
+/// What a ball bounced off.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BounceSurface {
+    Paddle(Player),
+    /// The top or bottom edge, as well as the left or right edge if they reflect the ball.
+    Wall,
+}
+
+/// Sent when a ball bounces off a paddle or a wall.
+pub struct BounceEvent {
+    pub ball: Entity,
+    pub surface: BounceSurface,
+    pub game: Entity,
+}
+
+/// Resets a game (every game if `None`): the scores are set to 0 and the paddles and balls are
+/// moved back to their start positions, additional balls are despawned.
+#[derive(Default)]
+pub struct ResetGameEvent(pub Option<Entity>);
+
+/// Sent when a ball crosses a goal line, right before the ball gets reset.
+pub struct GoalEvent {
+    pub scorer: Player,
+    /// Position of the ball (relative to the game's center) when it crossed the goal line.
+    pub ball_pos: Vec3,
+    pub game: Entity,
+}
+
+/// Position and velocity of a single ball, as stored in [`PongState`].
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct BallState {
+    /// Position relative to the game's center.
+    pub position: Vec3,
+    pub velocity: Vec2,
+}
+
+/// Read-only summary of the running game, synced from the game entities once per frame.
+///
+/// The syncing is opt-in: insert `PongState::default()` as a component of a `PongGame` entity to
+/// enable it for that game. Any changes made to it are overwritten in the next frame and have no
+/// effect on the game.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PongState {
+    scores: (u16, u16),
+    balls: Vec<BallState>,
+    paddles: (Vec3, Vec3),
+}
+
+impl PongState {
+    pub fn score_of(&self, player: &Player) -> u16 {
+        match player {
+            Player::Player1 => self.scores.0,
+            Player::Player2 => self.scores.1,
+        }
+    }
+    /// Position of the players paddle relative to the game's center.
+    pub fn paddle_position_of(&self, player: &Player) -> Vec3 {
+        match player {
+            Player::Player1 => self.paddles.0,
+            Player::Player2 => self.paddles.1,
+        }
+    }
+    pub fn balls(&self) -> &[BallState] {
+        &self.balls
+    }
+}
+
+/// The vertical positions of the paddles (relative to the game's center), synced once per frame.
+///
+/// Like the `PongState`, the syncing is opt-in: insert `PaddlePositions::default()` as a component of
+/// a `PongGame` entity to enable it. Any changes made to it are overwritten in the next frame.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct PaddlePositions {
+    pub p1: f32,
+    pub p2: f32,
+}
+
+/// Statistics of the current game (e.g. for a summary after the game is over), which are reset
+/// with the game.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PongStats {
+    bounces: u32,
+    rally: u32,
+    longest_rally: u32,
+    max_ball_speed: f32,
+    points: (u16, u16),
+}
+
+impl PongStats {
+    /// The number of times a ball bounced off a paddle or a wall.
+    pub fn bounces(&self) -> u32 {
+        self.bounces
+    }
+    /// The most paddle hits between two goals.
+    pub fn longest_rally(&self) -> u32 {
+        self.longest_rally
+    }
+    /// The highest speed any ball reached.
+    pub fn max_ball_speed(&self) -> f32 {
+        self.max_ball_speed
+    }
+    pub fn points_of(&self, player: &Player) -> u16 {
+        match player {
+            Player::Player1 => self.points.0,
+            Player::Player2 => self.points.1,
+        }
+    }
+}
+
+pub type IsBall = (With<Ball>, Without<Player>);
+pub type IsPlayer = (With<Player>, Without<Ball>);
+
+/// Whether a request for a game (for every game if `None`) applies to the given game.
+fn targets(request: Option<Entity>, game: Entity) -> bool {
+    request.is_none_or(|target| target == game)
+}
+
+/// The scores of the players of a game (scores.0 is for player 1; scores.1 is for player 2).
+fn scores_of<'a>(game: Entity, players: impl Iterator<Item = (&'a Parent, &'a Player, &'a Score)>) -> (u16, u16) {
+    let mut scores = (0, 0);
+    for (_, player, score) in players.filter(|(parent, ..)| parent.0 == game) {
+        match player {
+            Player::Player1 => scores.0 = score.0,
+            Player::Player2 => scores.1 = score.0,
+        }
+    }
+    scores
+}
+
+/// Does nothing, it only carries the `PongSystem::PreInput` label, so the input systems can be
+/// ordered after it, even if no other system has the label.
+fn pre_input() {}
+
+/// Spawns the game with the options of the `PongOptions` resource (the default options, if there is
+/// no such resource), unless the plugin was created `without_default_game`.
+fn spawn_default_game(mut commands: Commands, pong_options: Option<Res<PongOptions>>) {
+    let options = match pong_options {
+        Some(opt) => opt.clone(),
+        None => {
+            commands.insert_resource(PongOptions::default());
+            PongOptions::default()
+        }
+    };
+    commands.spawn_bundle(PongGameBundle::new(options)).insert(DefaultGame);
+}
+
+/// Copies changes of the `PongOptions` resource to the game spawned by the plugin. The score
+/// display options are kept, they are changed with the `SpawnScoreDisplayEvent`.
+fn sync_default_options(
+    pong_options: Option<Res<PongOptions>>,
+    mut games: Query<&mut PongOptions, With<DefaultGame>>,
+) {
+    let pong_options = match pong_options {
+        Some(opt) if opt.is_changed() => opt,
+        _ => return,
+    };
+    for mut options in games.iter_mut() {
+        let score_display_options = options.score_display_options.take();
+        *options = PongOptions { score_display_options, ..pong_options.clone() };
+    }
+}
+
+/// Sets up the games spawned since the last frame: inserts the state of the game and spawns the
+/// paddles, the ball, the border and the score display as children.
+fn setup_games(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    callbacks: Res<PongCallbacks>,
+    games: Query<(Entity, &PongOptions), Added<PongGame>>,
+) {
+    for (game, options) in games.iter() {
+        let mut root = commands.entity(game);
+        root.insert(BallSpeedupTimer(Timer::from_seconds(options.ball.speedup_time, true)))
+            .insert(AwaitingFirstServe(options.game.wait_for_ready))
+            .insert(GameOverState::default())
+            .insert(Streak::default())
+            .insert(PongStats::default())
+            .insert(MatchElapsed::default())
+            .insert(Overtime::default())
+            .insert(TimeSinceGoal::default());
+        if let Some(time_limit) = options.game.time_limit {
+            root.insert(MatchTimer(Timer::from_seconds(time_limit, false)));
+        }
+
+        // Everything else is placed relative to the root, so rotating it changes the orientation of the whole game.
+        let root_transform = Transform {
+            translation: options.game.position,
+            rotation: options.game.orientation.rotation(),
+            ..Default::default()
+        };
+        match &options.game.background {
+            Background::Color(color) => {
+                root.insert_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: *color,
+                        custom_size: Some(options.game.size),
+                        ..Default::default()
+                    },
+                    transform: root_transform,
+                    ..Default::default()
+                });
+            }
+            Background::Texture(texture) => {
+                root.insert_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(options.game.size),
+                        ..Default::default()
+                    },
+                    texture: texture.clone(),
+                    transform: root_transform,
+                    ..Default::default()
+                });
+            }
+            // The root still needs a transform, so it can be used as anchor for the children.
+            Background::None => {
+                root.insert(root_transform)
+                    .insert(GlobalTransform::default());
+            }
+        }
+
+        root.with_children(|parent| {
+            for player in [Player::Player1, Player::Player2].iter().filter(|p| !options.wall_for(p)) {
+                callbacks.init_paddle(*player, &mut parent.spawn_bundle(PlayerBundle::new(*player, options)));
+            }
+            let velocity = if options.game.wait_for_ready {
+                Vec2::ZERO
+            } else {
+                options.serve_velocity(options.server_after(0))
+            };
+            callbacks.init_ball(&mut parent.spawn_bundle(BallBundle::new(options).with_velocity(velocity)));
+            if let Some(border) = options.game.border {
+                for (i, (position, size)) in border.sides(options.game.size).iter().enumerate() {
+                    parent.spawn().insert(BorderSide(i))
+                        .insert_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                color: border.color,
+                                custom_size: Some(*size),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(position.extend(options.game.position.z + 1.)),
+                            ..Default::default()
+                        });
+                }
+            }
+            if let Some(score_options) = &options.score_display_options {
+                spawn_score_display(parent, &asset_server, options, score_options, (0, 0));
+            }
+        });
+    }
+}
+
+fn spawn_requested_balls(
+    mut commands: Commands,
+    callbacks: Res<PongCallbacks>,
+    mut requests: EventReader<SpawnBallEvent>,
+    mut rejected_writer: EventWriter<BallSpawnRejectedEvent>,
+    games: Query<(Entity, &PongOptions), With<PongGame>>,
+    balls: Query<&Parent, IsBall>,
+) {
+    let mut ball_counts = HashMap::<Entity, usize>::default();
+    for parent in balls.iter() {
+        *ball_counts.entry(parent.0).or_default() += 1;
+    }
+
+    for request in requests.iter() {
+        for (game, options) in games.iter().filter(|(game, _)| targets(request.game, *game)) {
+            let ball_count = ball_counts.entry(game).or_default();
+            if *ball_count >= options.ball.max_balls {
+                rejected_writer.send(BallSpawnRejectedEvent { game });
+                continue;
+            }
+            *ball_count += 1;
+
+            let mut bundle = BallBundle::new(options);
+            if let Some(position) = request.position {
+                bundle = bundle.with_position(Vec2::new(position.x, Ball::clamp_y(options, position.y)));
+            }
+            if let Some(velocity) = request.velocity {
+                bundle = bundle.with_velocity(velocity);
+            }
+            commands.entity(game).with_children(|parent| {
+                callbacks.init_ball(&mut parent.spawn_bundle(bundle));
+            });
+        }
+    }
+}
+
+fn set_ball_velocity(
+    mut event_reader: EventReader<SetBallVelocityEvent>,
+    games: Query<&PongOptions, With<PongGame>>,
+    mut balls: Query<(Entity, &Parent, &mut Velocity), IsBall>,
+) {
+    for SetBallVelocityEvent { entity, velocity } in event_reader.iter() {
+        for (ball, parent, mut vel) in balls.iter_mut() {
+            if entity.is_some_and(|e| e != ball) {
+                continue;
+            }
+            if let Ok(options) = games.get(parent.0) {
+                vel.0 = options.ball.clamp_speed(*velocity);
+            }
+        }
+    }
+}
+
+/// The components of a ball, which are reset when the ball gets served again.
+type ServedBall<'a> = (
+    &'a mut Transform, &'a mut Velocity, &'a mut LastTouchedBy, &'a mut ServeRamp, &'a mut BallSize,
+    &'a mut Sprite, &'a mut PaddleBounceCooldown, &'a mut WallBounceCooldown, &'a mut Interpolation,
+);
+
+/// The balls with their game and the components of `ServedBall`, for the systems which serve them again.
+type ServedBalls<'w, 's> = Query<'w, 's, (
+    Entity, &'static Parent, &'static mut Transform, &'static mut Velocity, &'static mut LastTouchedBy,
+    &'static mut ServeRamp, &'static mut BallSize, &'static mut Sprite, &'static mut PaddleBounceCooldown,
+    &'static mut WallBounceCooldown, &'static mut Interpolation,
+), IsBall>;
+
+/// Moves a ball to the given position and serves it with the given velocity, everything the ball
+/// picked up during the rally (its size, the serve ramp, the bounce cooldowns, ...) is reset.
+fn reset_ball_state(options: &PongOptions, ball: ServedBall, position: Vec3, velocity: Vec2) {
+    let (trans, vel, last_touched, ramp, size, sprite, paddle_cooldown, wall_cooldown, interpolation) = ball;
+    trans.translation = position;
+    interpolation.snap(position);
+    vel.0 = velocity;
+    last_touched.0 = None;
+    ramp.reset();
+    size.0 = options.ball.size;
+    sprite.custom_size = Some(options.ball.size);
+    paddle_cooldown.0 = 0;
+    wall_cooldown.0 = 0.;
+}
+
+fn reset_requested_balls(
+    mut commands: Commands,
+    mut event_reader: EventReader<ResetBallEvent>,
+    mut balls: ServedBalls,
+    games: Query<(&PongOptions, &AwaitingFirstServe)>,
+    players: Query<(&Parent, &Player, &Score), IsPlayer>,
+) {
+    for ResetBallEvent { entity } in event_reader.iter() {
+        for (
+            ball, parent, mut trans, mut vel, mut last_touched, mut ramp, mut size, mut sprite, mut paddle_cooldown,
+            mut wall_cooldown, mut interpolation
+        ) in balls.iter_mut() {
+            if entity.is_some_and(|e| e != ball) {
+                continue;
+            }
+            let (options, awaiting) = match games.get(parent.0) {
+                Ok(game) => game,
+                Err(_) => continue,
+            };
+            let velocity = if awaiting.0 {
+                Vec2::ZERO
+            } else {
+                let (score1, score2) = scores_of(parent.0, players.iter());
+                options.serve_velocity(options.server_after(score1 + score2))
+            };
+            reset_ball_state(
+                options,
+                (
+                    &mut trans, &mut vel, &mut last_touched, &mut ramp, &mut size, &mut sprite, &mut paddle_cooldown,
+                    &mut wall_cooldown, &mut interpolation
+                ),
+                Ball::start_position(options),
+                velocity,
+            );
+            commands.entity(ball).remove::<PendingReset>();
+        }
+    }
+}
+
+/// Pauses all games while the window is unfocused, if `GameOptions::pause_on_focus_loss` is set for
+/// any of them. The manual pause and the focus pause are tracked separately, so a manual pause,
+/// whether it happened before or during the focus loss, is kept after the window is focused again.
+fn pause_on_focus_loss(
+    games: Query<&PongOptions, With<PongGame>>,
+    mut paused: ResMut<PongPaused>,
+    mut paused_manually: Local<bool>,
+    mut paused_by_focus: Local<bool>,
+    mut focus_reader: EventReader<WindowFocused>,
+) {
+    if !games.iter().any(|options| options.game.pause_on_focus_loss) {
+        return;
+    }
+
+    // The own writes of this system don't count as changes, so every change is a manual one. A
+    // manual unpause also ends the focus pause.
+    if paused.is_changed() {
+        *paused_manually = paused.0;
+        *paused_by_focus &= paused.0;
+    }
+    for event in focus_reader.iter() {
+        *paused_by_focus = !event.focused;
+    }
+
+    let pause = *paused_manually || *paused_by_focus;
+    if paused.0 != pause {
+        paused.0 = pause;
+    }
+}
+
+/// Applies changes of `GameOptions::size` to the already spawned entities of a game, the other
+/// systems read the size every frame anyway.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn resize_game(
+    mut last_sizes: Local<HashMap<Entity, Vec2>>,
+    mut games: Query<(Entity, &PongOptions, Option<&mut Sprite>), (With<PongGame>, Changed<PongOptions>)>,
+    mut score_texts: Query<(&Parent, &mut Transform), (With<ScoreDisplayText>, Without<Player>)>,
+    mut score_digits: Query<
+        (&Parent, &DigitOffset, &mut Transform), (With<ScoreDigit>, Without<Player>, Without<ScoreDisplayText>)
+    >,
+    mut speed_texts: Query<
+        (&Parent, &mut Transform), (With<BallSpeedText>, Without<Player>, Without<ScoreDisplayText>, Without<ScoreDigit>)
+    >,
+    mut players: Query<(&Parent, &Player, &mut Transform), IsPlayer>,
+    mut balls: Query<
+        (&Parent, &BallSize, &mut Transform),
+        (With<Ball>, Without<Player>, Without<ScoreDisplayText>, Without<ScoreDigit>, Without<BallSpeedText>, Without<BorderSide>)
+    >,
+    mut borders: Query<
+        (&Parent, &BorderSide, &mut Transform, &mut Sprite),
+        (Without<Player>, Without<PongGame>, Without<ScoreDisplayText>, Without<ScoreDigit>, Without<BallSpeedText>)
+    >,
+) {
+    for (game, options, background) in games.iter_mut() {
+        if let Some(mut sprite) = background {
+            sprite.custom_size = Some(options.game.size);
+        }
+        if let Some(score_options) = &options.score_display_options {
+            for (_, mut trans) in score_texts.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                trans.translation.y = options.game.size.y / 2. - score_options.font_size * (2. / 3.);
+            }
+            if let ScoreDisplay::Images { digit_size, .. } = score_options.display {
+                for (_, offset, mut trans) in score_digits.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+                    trans.translation.y = options.game.size.y / 2. - digit_size.y * (2. / 3.) + offset.0.y;
+                }
+            }
+            for (_, mut trans) in speed_texts.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                trans.translation.y = score_options.ball_speed_y(options.game.size);
+            }
+        }
+        // The paddles are moved into the new bounds, in case the game got smaller.
+        let max_y = (options.game.size.y - options.player.size.y).max(0.) / 2.;
+        for (_, player, mut trans) in players.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+            trans.translation.x = player.start_position(options).x;
+            trans.translation.y = trans.translation.y.clamp(-max_y, max_y);
+        }
+        // So are the balls, but only if the size changed, other changes of the options must not move
+        // them. A ball is kept in front of the paddles, it would score right away on a goal line.
+        if last_sizes.insert(game, options.game.size) != Some(options.game.size) {
+            let paddle_front = options.game.size.x / 2. - options.player.size.x * 1.5;
+            for (_, size, mut trans) in balls.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+                let half_size = options.ball.shape.collision_size(size.0) / 2.;
+                let max_x = (paddle_front - half_size.x).max(0.);
+                let max_y = (options.game.size.y / 2. - half_size.y).max(0.);
+                trans.translation.x = trans.translation.x.clamp(-max_x, max_x);
+                trans.translation.y = trans.translation.y.clamp(-max_y, max_y);
+            }
+        }
+        if let Some(border) = options.game.border {
+            let sides = border.sides(options.game.size);
+            for (_, BorderSide(i), mut trans, mut sprite) in borders.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+                let (position, size) = sides[*i];
+                trans.translation.x = position.x;
+                trans.translation.y = position.y;
+                sprite.custom_size = Some(size);
+            }
+        }
+    }
+}
+
+/// Spawns the score display (and the ball speed text, if enabled) showing the given scores
+/// (scores.0 is for player 1; scores.1 is for player 2).
+fn spawn_score_display(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    options: &PongOptions,
+    score_options: &ScoreDisplayOptions,
+    scores: (u16, u16),
+) {
+    match &score_options.display {
+        ScoreDisplay::Text => {
+            let text_style = TextStyle {
+                font: asset_server.load(score_options.font_path),
+                font_size: score_options.font_size,
+                color: score_options.font_color,
+            };
+            let section = |s: String, color: Color| TextSection {
+                value: s,
+                style: TextStyle { color, ..text_style.clone() },
+            };
+
+            parent.spawn().insert(ScoreDisplayText)
+                .insert_bundle(Text2dBundle {
+                    text: Text {
+                        sections: vec![
+                            section(options.displayed_score(scores.0).to_string(), options.score_color_for(&Player::Player1)),
+                            section(":".into(), score_options.font_color),
+                            section(options.displayed_score(scores.1).to_string(), options.score_color_for(&Player::Player2)),
+                        ],
+                        alignment: TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    },
+                    // The text is rotated back, so it stays readable in every orientation.
+                    transform: Transform {
+                        translation: Vec3::new(
+                            0.,
+                            options.game.size.y / 2. - score_options.font_size * (2. / 3.),
+                            options.game.position.z + 1.
+                        ),
+                        rotation: options.game.orientation.rotation().inverse(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+        }
+        ScoreDisplay::Images { atlas, digit_size } => {
+            spawn_score_digits(parent, options, atlas, *digit_size, Player::Player1, scores.0);
+            spawn_score_digits(parent, options, atlas, *digit_size, Player::Player2, scores.1);
+        }
+    }
+
+    if score_options.show_ball_speed {
+        parent.spawn().insert(BallSpeedText)
+            .insert_bundle(Text2dBundle {
+                text: Text::with_section(
+                    "0",
+                    TextStyle {
+                        font: asset_server.load(score_options.font_path),
+                        font_size: score_options.font_size,
+                        color: score_options.font_color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform {
+                    translation: Vec3::new(
+                        0.,
+                        score_options.ball_speed_y(options.game.size),
+                        options.game.position.z + 1.
+                    ),
+                    rotation: options.game.orientation.rotation().inverse(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+    }
+}
+
+/// Replaces (or removes) the score display of a game at runtime, its `PongOptions` are updated accordingly.
+#[allow(clippy::type_complexity)]
+fn toggle_score_display(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut spawn_reader: EventReader<SpawnScoreDisplayEvent>,
+    mut despawn_reader: EventReader<DespawnScoreDisplayEvent>,
+    mut games: Query<(Entity, &mut PongOptions), With<PongGame>>,
+    players: Query<(&Parent, &Player, &Score), IsPlayer>,
+    displays: Query<(Entity, &Parent), Or<(With<ScoreDisplayText>, With<ScoreDigit>, With<BallSpeedText>)>>,
+) {
+    let despawns: Vec<Option<Entity>> = despawn_reader.iter().map(|DespawnScoreDisplayEvent(game)| *game).collect();
+    let spawns: Vec<&SpawnScoreDisplayEvent> = spawn_reader.iter().collect();
+    if despawns.is_empty() && spawns.is_empty() {
+        return;
+    }
+
+    for (game, mut options) in games.iter_mut() {
+        let despawn = despawns.iter().any(|target| targets(*target, game));
+        let spawn = spawns.iter().rev().find(|event| targets(event.game, game));
+        if !despawn && spawn.is_none() {
+            continue;
+        }
+
+        for (entity, _) in displays.iter().filter(|(_, parent)| parent.0 == game) {
+            commands.entity(entity).despawn_recursive();
+        }
+        options.score_display_options = spawn.map(|event| event.options.clone());
+
+        if let Some(score_options) = &options.score_display_options {
+            let scores = scores_of(game, players.iter());
+            commands.entity(game).with_children(|parent| {
+                spawn_score_display(parent, &asset_server, &options, score_options, scores);
+            });
+        }
+    }
+}
+
+/// Spawns the digits of a score, player 1's score is right-aligned left of the center and player
+/// 2's score left-aligned right of the center.
+fn spawn_score_digits(
+    parent: &mut ChildBuilder,
+    options: &PongOptions,
+    atlas: &Handle<TextureAtlas>,
+    digit_size: Vec2,
+    player: Player,
+    points: u16,
+) {
+    let digits: Vec<u32> = options.displayed_score(points).to_string().chars().filter_map(|c| c.to_digit(10)).collect();
+    let y = options.game.size.y / 2. - digit_size.y * (2. / 3.);
+    // Like the score text, the digits are rotated back and lined up along the x axis of the screen,
+    // so they stay readable in every orientation.
+    let rotation = options.game.orientation.rotation().inverse();
+    for (i, digit) in digits.iter().enumerate() {
+        let x = match player {
+            Player::Player1 => -((digits.len() - i) as f32) * digit_size.x,
+            Player::Player2 => (i + 1) as f32 * digit_size.x,
+        };
+        let offset = rotation * Vec3::new(x, 0., 0.);
+        parent.spawn().insert(ScoreDigit(player))
+            .insert(DigitOffset(offset))
+            .insert_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index: *digit as usize,
+                    color: match &options.score_display_options {
+                        Some(score_options) if score_options.color_per_player => options.color_for(&player),
+                        _ => Color::WHITE,
+                    },
+                    custom_size: Some(digit_size),
+                    ..Default::default()
+                },
+                texture_atlas: atlas.clone(),
+                transform: Transform {
+                    translation: Vec3::new(0., y, options.game.position.z + 1.) + offset,
+                    rotation,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+    }
+}
+
+fn wait_for_first_serve(
+    mut games: Query<(Entity, &PongOptions, &mut AwaitingFirstServe)>,
+    key_input: Res<Input<KeyCode>>,
+    mut balls: Query<(&Parent, &mut Velocity, &mut ServeRamp), IsBall>,
+    players: Query<(&Parent, &Player, &Score), IsPlayer>,
+) {
+    for (game, options, mut awaiting) in games.iter_mut() {
+        if !awaiting.0 {
+            continue;
+        }
+
+        let ready = [Player::Player1, Player::Player2].iter()
+            .any(|player| key_input.just_pressed(options.up_for(player)));
+        if ready {
+            awaiting.0 = false;
+            let (score1, score2) = scores_of(game, players.iter());
+            let server = options.server_after(score1 + score2);
+            for (_, mut vel, mut ramp) in balls.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+                vel.0 = options.serve_velocity(server);
+                ramp.reset();
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_player_input(
+    time: Res<Time>,
+    key_input: Res<Input<KeyCode>>,
+    player_input: Res<PlayerInput>,
+    paused: Res<PongPaused>,
+    mut dash_writer: EventWriter<DashEvent>,
+    mut center_writer: EventWriter<CenterPaddleEvent>,
+    games: Query<(&PongOptions, &GameOverState, Option<&PlayerInput>)>,
+    mut players: Query<(
+        &Parent, &Player, &mut Transform, &mut Dash, &mut StepCooldown, Option<&InvertedControls>, Option<&SpeedBoost>
+    )>
+) {
+    if paused.0 {
+        return;
+    }
+    let delta = time.delta_seconds();
+
+    for (parent, player, mut transform, mut dash, mut step_cooldown, inverted, boost) in players.iter_mut() {
+        let game = parent.0;
+        let (options, game_over, game_input) = match games.get(game) {
+            Ok(game) => game,
+            Err(_) => continue,
+        };
+        if game_over.over {
+            continue;
+        }
+        let hps = options.player.size.y / 2.;
+        let hgs = options.game.size.y / 2.;
+
+        dash.remaining = (dash.remaining - delta).max(0.);
+        dash.cooldown = (dash.cooldown - delta).max(0.);
+        if let Some(dash_key) = options.dash_for(player) {
+            if key_input.just_pressed(dash_key) && dash.cooldown <= 0. {
+                dash.remaining = DASH_DURATION;
+                dash.cooldown = options.player.dash_cooldown;
+                dash_writer.send(DashEvent { player: *player, game });
+            }
+        }
+
+        let controller = options.controller_for(player);
+        let direction = match controller {
+            Controller::Keyboard => {
+                let pressed = |key| if key_input.pressed(key) { 1. } else { 0. };
+                pressed(options.up_for(player)) - pressed(options.down_for(player))
+            }
+            Controller::External => game_input.unwrap_or(&*player_input).direction_for(player).clamp(-1., 1.),
+        };
+        let direction = if inverted.is_some() { -direction } else { direction };
+
+        let mut movement = match options.player.movement_mode {
+            MovementMode::Continuous => options.player.speed * delta,
+            MovementMode::Stepped { step, cooldown } => {
+                step_cooldown.0 = (step_cooldown.0 - delta).max(0.);
+                let just_pressed = controller == Controller::Keyboard
+                    && (key_input.just_pressed(options.up_for(player)) || key_input.just_pressed(options.down_for(player)));
+                if direction != 0. && (just_pressed || step_cooldown.0 <= 0.) {
+                    step_cooldown.0 = cooldown;
+                    step
+                } else {
+                    0.
+                }
+            }
+        };
+        if dash.remaining > 0. {
+            movement *= options.player.dash_multiplier;
+        }
+        if let Some(boost) = boost {
+            movement *= boost.multiplier;
+        }
+        // The movement is clamped, so the paddle can't leave the game.
+        let y = &mut transform.translation.y;
+        if direction > 0. {
+            *y = (*y + movement * direction).min(hgs - hps);
+        } else if direction < 0. {
+            *y = (*y + movement * direction).max(-hgs + hps);
+        } else if let (Controller::Keyboard, Some(center_key)) = (controller, options.center_for(player)) {
+            if key_input.just_pressed(center_key) {
+                center_writer.send(CenterPaddleEvent { player: *player, game });
+            }
+            if key_input.pressed(center_key) {
+                *y = if options.player.center_instantly {
+                    0.
+                } else {
+                    *y - y.signum() * y.abs().min(options.player.speed * delta)
+                };
+            }
+        }
+    }
+}
+
+fn invert_controls(
+    mut commands: Commands,
+    mut event_reader: EventReader<InvertControlsEvent>,
+    mut inverted_writer: EventWriter<ControlsInvertedEvent>,
+    players: Query<(Entity, &Parent, &Player)>,
+) {
+    for InvertControlsEvent { player, duration, game } in event_reader.iter() {
+        for (entity, parent, p) in players.iter() {
+            if p == player && targets(*game, parent.0) {
+                commands.entity(entity).insert(InvertedControls(Timer::from_seconds(*duration, false)));
+                inverted_writer.send(ControlsInvertedEvent { player: *player, game: parent.0 });
+            }
+        }
+    }
+}
+
+fn boost_speed(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpeedBoostEvent>,
+    mut applied_writer: EventWriter<SpeedBoostAppliedEvent>,
+    players: Query<(Entity, &Parent, &Player)>,
+) {
+    for SpeedBoostEvent { player, multiplier, duration, game } in event_reader.iter() {
+        for (entity, parent, p) in players.iter() {
+            if p == player && targets(*game, parent.0) {
+                commands.entity(entity).insert(SpeedBoost {
+                    multiplier: *multiplier,
+                    timer: Timer::from_seconds(*duration, false),
+                });
+                applied_writer.send(SpeedBoostAppliedEvent { player: *player, game: parent.0 });
+            }
+        }
+    }
+}
+
+fn expire_speed_boosts(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<PongPaused>,
+    mut expired_writer: EventWriter<SpeedBoostExpiredEvent>,
+    mut players: Query<(Entity, &Parent, &Player, &mut SpeedBoost)>,
+) {
+    if paused.0 {
+        return;
+    }
+    for (entity, parent, player, mut boost) in players.iter_mut() {
+        if boost.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<SpeedBoost>();
+            expired_writer.send(SpeedBoostExpiredEvent { player: *player, game: parent.0 });
+        }
+    }
+}
+
+fn expire_inverted_controls(
+    mut commands: Commands,
+    time: Res<Time>,
+    paused: Res<PongPaused>,
+    mut restored_writer: EventWriter<ControlsRestoredEvent>,
+    mut players: Query<(Entity, &Parent, &Player, &mut InvertedControls)>,
+) {
+    if paused.0 {
+        return;
+    }
+    for (entity, parent, player, mut inverted) in players.iter_mut() {
+        if inverted.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<InvertedControls>();
+            restored_writer.send(ControlsRestoredEvent { player: *player, game: parent.0 });
+        }
+    }
+}
+
+fn tick_match_elapsed(
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    paused: Res<PongPaused>,
+    mut games: Query<(&mut MatchElapsed, &GameOverState, &AwaitingFirstServe)>,
+) {
+    if paused.0 {
+        return;
+    }
+    for (mut elapsed, game_over, awaiting) in games.iter_mut() {
+        // The game only starts running with the first serve.
+        if !game_over.over && !awaiting.0 {
+            elapsed.0 += step.delta(&time);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn speedup_ball(
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    freeze: Res<FreezeBall>,
+    freeze_speedup: Res<FreezeSpeedup>,
+    paused: Res<PongPaused>,
+    mut games: Query<(Entity, &PongOptions, &mut BallSpeedupTimer, &MatchElapsed, &GameOverState, &AwaitingFirstServe)>,
+    mut ball_velocities: Query<(&Parent, &mut Velocity), IsBall>,
+) {
+    if freeze.0 || freeze_speedup.0 || paused.0 {
+        return;
+    }
+    for (game, options, mut ball_timer, elapsed, game_over, awaiting) in games.iter_mut() {
+        if game_over.over || awaiting.0 || options.ball.speedup_mode == SpeedupMode::PerHit {
+            continue;
+        }
+        if !ball_timer.0.tick(Duration::from_secs_f32(step.delta(&time))).just_finished() {
+            continue;
+        }
+
+        for (_, mut vel) in ball_velocities.iter_mut().filter(|(parent, _)| parent.0 == game) {
+            vel.0 = options.ball.clamp_speed(vel.0 * options.ball.speedup_factor_at(elapsed.0));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_ball_velocity(
+    mut commands: Commands,
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    freeze: Res<FreezeBall>,
+    freeze_speedup: Res<FreezeSpeedup>,
+    paused: Res<PongPaused>,
+    mut bounce_writer: EventWriter<BounceEvent>,
+    mut perfect_hit_writer: EventWriter<PerfectHitEvent>,
+    games: Query<(&PongOptions, &GameOverState, &MatchElapsed)>,
+    mut balls: Query<(
+        Entity, &Parent, &mut Transform, &mut Velocity, &mut PaddleBounceCooldown, &mut WallBounceCooldown,
+        &mut LastTouchedBy, &mut ServeRamp, &mut BallSize, &mut Sprite, &mut Interpolation
+    ), IsBall>,
+    players: Query<(Entity, &Parent, &Player, &Transform), IsPlayer>,
+) {
+    if freeze.0 || paused.0 {
+        return;
+    }
+    let delta = step.delta(&time);
+
+    for (
+        ball, parent, mut trans, mut vel, mut cooldown, mut wall_cooldown, mut last_touched, mut ramp, mut size,
+        mut sprite, mut interpolation
+    ) in balls.iter_mut() {
+        let game = parent.0;
+        let (options, game_over, elapsed) = match games.get(game) {
+            Ok(game) => game,
+            Err(_) => continue,
+        };
+        if game_over.over {
+            continue;
+        }
+        let hgs = options.game.size.y / 2.;
+        let ball_size = options.ball.shape.collision_size(size.0);
+        let hbs = ball_size.y / 2.;
+        // Distance between the centers of a paddle and the ball when they are touching.
+        let touching = (options.player.size + ball_size) / 2.;
+        let previous = trans.translation;
+
+        let slowdown = match options.game.center_slow {
+            Some((half_width, factor)) if trans.translation.x.abs() < half_width => factor,
+            _ => 1.,
+        } * ramp.tick(delta);
+        if options.ball.friction > 0. {
+            vel.0 = options.ball.clamp_speed(vel.0 * (1. - options.ball.friction * delta).max(0.));
+        }
+        trans.translation.x += vel.0.x * slowdown * delta;
+        trans.translation.y += vel.0.y * slowdown * delta;
+
+        if cooldown.0 > 0 {
+            cooldown.0 -= 1;
+        } else {
+            // If the ball touches both paddles at once, only the collision with the paddle it moves
+            // towards (the nearest one, if it moves towards both) is resolved, so the ball doesn't
+            // get flipped twice.
+            let hit = players.iter()
+                .filter(|(_, parent, ..)| parent.0 == game)
+                .filter_map(|(paddle, _, player, p_trans)| {
+                    hit_paddle(p_trans.translation, options.player.size, previous, trans.translation, ball_size)
+                        .map(|normal| (paddle, player, p_trans.translation, normal))
+                })
+                .filter(|(_, player, _, normal)| {
+                    // Player 1 is on the left, so the front of their paddle faces to the right.
+                    let front = match player {
+                        Player::Player1 => 1.,
+                        Player::Player2 => -1.,
+                    };
+                    !options.player.front_face_only || normal.x == front
+                })
+                .min_by(|(_, _, a, _), (_, _, b, _)| {
+                    let key = |p: &Vec3| {
+                        let moving_away = (p.x - trans.translation.x) * vel.0.x < 0.;
+                        (moving_away, p.distance_squared(trans.translation))
+                    };
+                    key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)
+                });
+            if let Some((paddle, player, p, normal)) = hit {
+                // The ball is moved out of the paddle and sent away from it, instead of just
+                // flipping its velocity, otherwise it might get stuck inside the paddle.
+                if normal.x != 0. {
+                    let side = normal.x;
+                    vel.0.x = vel.0.x.abs() * side;
+                    trans.translation.x = p.x + touching.x * side;
+                    if options.ball.shape == BallShape::Puck && trans.translation.y != p.y {
+                        let speed = vel.0.length();
+                        let offset = (trans.translation.y - p.y).signum();
+                        vel.0.y += vel.0.x.abs() * PUCK_DEFLECTION * offset;
+                        vel.0 = vel.0.normalize_or_zero() * speed;
+                    }
+                    if let Some((threshold, bonus)) = options.player.perfect_hit {
+                        if (trans.translation.y - p.y).abs() <= threshold {
+                            vel.0 *= bonus;
+                            perfect_hit_writer.send(PerfectHitEvent { ball, player: *player, game });
+                        }
+                    }
+                    if options.game.impact_marks {
+                        // The mark is placed on the hit side of the paddle, at the height of the ball.
+                        let contact = Vec3::new(
+                            side * options.player.size.x / 2.,
+                            (trans.translation.y - p.y).clamp(-options.player.size.y / 2., options.player.size.y / 2.),
+                            0.1,
+                        );
+                        commands.entity(paddle).with_children(|parent| {
+                            parent.spawn()
+                                .insert(ImpactMark(Timer::from_seconds(IMPACT_MARK_LIFETIME, false)))
+                                .insert_bundle(SpriteBundle {
+                                    sprite: Sprite {
+                                        color: options.ball.color,
+                                        custom_size: Some(Vec2::splat(IMPACT_MARK_SIZE)),
+                                        ..Default::default()
+                                    },
+                                    transform: Transform::from_translation(contact),
+                                    ..Default::default()
+                                });
+                        });
+                    }
+                } else {
+                    let side = normal.y;
+                    vel.0.y = vel.0.y.abs() * side;
+                    trans.translation.y = p.y + touching.y * side;
+                }
+                if options.ball.speedup_mode != SpeedupMode::Timed && !freeze_speedup.0 {
+                    vel.0 *= options.ball.speedup_factor_at(elapsed.0);
+                }
+                vel.0 = options.ball.clamp_speed(vel.0);
+                if let Some((growth, max_size)) = options.ball.rally_grow {
+                    size.0 = (size.0 + Vec2::splat(growth)).min(Vec2::splat(max_size)).max(options.ball.size);
+                    sprite.custom_size = Some(size.0);
+                }
+                cooldown.0 = PADDLE_BOUNCE_COOLDOWN;
+                last_touched.0 = Some(*player);
+                bounce_writer.send(BounceEvent { ball, surface: BounceSurface::Paddle(*player), game });
+            }
+        }
+
+        if options.game.wrap_vertically {
+            // The ball wraps only after it fully left the game, so it doesn't pop up on the other side.
+            if trans.translation.y - hbs >= hgs && vel.0.y > 0. {
+                trans.translation.y = -hgs - hbs;
+                interpolation.snap(trans.translation);
+            } else if trans.translation.y + hbs <= -hgs && vel.0.y < 0. {
+                trans.translation.y = hgs + hbs;
+                interpolation.snap(trans.translation);
+            }
+            continue;
+        }
+        wall_cooldown.0 = (wall_cooldown.0 - delta).max(0.);
+        let direction = if trans.translation.y + hbs >= hgs {   // Ball hits top
+            trans.translation.y = hgs - hbs;
+            -1.
+        } else if trans.translation.y - hbs <= -hgs {           // Ball hits bottom
+            trans.translation.y = -hgs + hbs;
+            1.
+        } else {
+            continue;
+        };
+        if wall_cooldown.0 <= 0. {
+            vel.0 = reflect_off_wall(options, vel.0, direction);
+            wall_cooldown.0 = options.ball.wall_bounce_interval;
+            bounce_writer.send(BounceEvent { ball, surface: BounceSurface::Wall, game });
+        }
+    }
+}
+
+/// Reflects the velocity off the top or bottom wall, so it points in the given vertical direction
+/// afterwards. The reflected velocity is rotated by `BallOptions::wall_randomness`, if set.
+fn reflect_off_wall(options: &PongOptions, velocity: Vec2, direction: f32) -> Vec2 {
+    let mut velocity = Vec2::new(velocity.x, -velocity.y);
+    if let Some(randomness) = options.ball.wall_randomness {
+        let (sin, cos) = randomness().sin_cos();
+        velocity = Vec2::new(velocity.x * cos - velocity.y * sin, velocity.x * sin + velocity.y * cos);
+    }
+    // The rotation must not send the ball back into the wall.
+    velocity.y = velocity.y.abs() * direction;
+    velocity
+}
+
+/// The side of a paddle which a ball moving from `previous` to `position` within the last step
+/// hit, as the direction in which the ball gets pushed out of the paddle. Unlike bevy's `collide`, the
+/// whole movement is checked, so a fast ball passing through the paddle within one step or a ball
+/// straddling a thinner paddle still hits the side it came from.
+fn hit_paddle(paddle: Vec3, paddle_size: Vec2, previous: Vec3, position: Vec3, ball_size: Vec2) -> Option<Vec2> {
+    // The paddle grown by the size of the ball, so the ball can be treated as a point.
+    let touching = (paddle_size + ball_size) / 2.;
+    let from = (previous - paddle).truncate();
+    let to = (position - paddle).truncate();
+    let movement = to - from;
+
+    if from.x.abs() < touching.x && from.y.abs() < touching.y {
+        // The ball was already inside (e.g. the paddle moved into it), so it's pushed out on the
+        // side it's closest to.
+        if !(to.x.abs() < touching.x && to.y.abs() < touching.y) {
+            return None;
+        }
+        let side = |offset: f32, movement: f32| match offset {
+            offset if offset != 0. => offset.signum(),
+            _ => -movement.signum(),
+        };
+        return if touching.x - to.x.abs() <= touching.y - to.y.abs() {
+            Some(Vec2::new(side(to.x, movement.x), 0.))
+        } else {
+            Some(Vec2::new(0., side(to.y, movement.y)))
+        };
+    }
+
+    // The times (as fraction of the step) at which the ball enters and leaves the paddle on
+    // each axis, the ball is inside while it's inside on both axes.
+    let slab = |from: f32, movement: f32, touching: f32| {
+        if movement == 0. {
+            if from.abs() < touching {
+                (f32::NEG_INFINITY, f32::INFINITY)
+            } else {
+                (f32::INFINITY, f32::NEG_INFINITY)
+            }
+        } else {
+            let (a, b) = ((-touching - from) / movement, (touching - from) / movement);
+            (a.min(b), a.max(b))
+        }
+    };
+    let (enter_x, leave_x) = slab(from.x, movement.x, touching.x);
+    let (enter_y, leave_y) = slab(from.y, movement.y, touching.y);
+    let (enter, leave) = (enter_x.max(enter_y), leave_x.min(leave_y));
+    if enter >= leave || !(0. ..=1.).contains(&enter) {
+        return None;
+    }
+    if enter_x >= enter_y {
+        Some(Vec2::new(-movement.x.signum(), 0.))
+    } else {
+        Some(Vec2::new(0., -movement.y.signum()))
+    }
+}
+
+/// Moves the balls back to their physics positions before a physics step. A ball which is not at
+/// its rendered position was moved by something else (e.g. a reset) and stays there.
+/// Whether the game of a ball or paddle has `GameOptions::interpolate` set.
+fn interpolates(games: &Query<&PongOptions>, parent: &Parent) -> bool {
+    games.get(parent.0).is_ok_and(|options| options.game.interpolate)
+}
+
+fn restore_physics_positions(
+    games: Query<&PongOptions>,
+    mut balls: Query<(&Parent, &mut Transform, &mut Interpolation), IsBall>,
+) {
+    for (_, mut trans, mut interpolation) in balls.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        if trans.translation != interpolation.rendered {
+            interpolation.previous = trans.translation;
+            interpolation.current = trans.translation;
+        }
+        trans.translation = interpolation.current;
+    }
+}
+
+/// Moves the paddles back to their actual positions before the input moves them. A paddle which
+/// is not at its rendered position was moved by something else (e.g. a reset) and stays there.
+fn restore_paddle_positions(
+    games: Query<&PongOptions>,
+    mut players: Query<(&Parent, &mut Transform, &mut Interpolation), IsPlayer>,
+) {
+    for (_, mut trans, mut interpolation) in players.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        if trans.translation != interpolation.rendered {
+            interpolation.previous = trans.translation;
+            interpolation.current = trans.translation;
+            interpolation.actual = trans.translation;
+        }
+        trans.translation = interpolation.actual;
+    }
+}
+
+fn record_physics_positions(
+    games: Query<&PongOptions>,
+    mut balls: Query<(&Parent, &Transform, &mut Interpolation), IsBall>,
+    mut players: Query<(&Parent, &Transform, &mut Interpolation), IsPlayer>,
+) {
+    for (_, trans, mut interpolation) in balls.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        interpolation.previous = interpolation.current;
+        interpolation.current = trans.translation;
+        interpolation.rendered = trans.translation;
+    }
+    for (_, trans, mut interpolation) in players.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        interpolation.previous = interpolation.current;
+        interpolation.current = trans.translation;
+    }
+}
+
+/// Renders the balls and paddles between their last two physics positions, depending on the
+/// progress towards the next fixed timestep. Like in `restore_physics_positions`, a ball which is
+/// not at its rendered position was moved by something else and stays there.
+fn interpolate_positions(
+    games: Query<&PongOptions>,
+    timesteps: Res<FixedTimesteps>,
+    mut balls: Query<(&Parent, &mut Transform, &mut Interpolation), IsBall>,
+    mut players: Query<(&Parent, &mut Transform, &mut Interpolation), IsPlayer>,
+) {
+    let alpha = match timesteps.get(PHYSICS_TIMESTEP) {
+        Some(state) => state.overstep_percentage() as f32,
+        None => return,
+    };
+    for (_, mut trans, mut interpolation) in balls.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        if trans.translation != interpolation.rendered {
+            interpolation.snap(trans.translation);
+        }
+        trans.translation = interpolation.previous.lerp(interpolation.current, alpha);
+        interpolation.rendered = trans.translation;
+    }
+    for (_, mut trans, mut interpolation) in players.iter_mut().filter(|(parent, ..)| interpolates(&games, parent)) {
+        interpolation.actual = trans.translation;
+        trans.translation = interpolation.previous.lerp(interpolation.current, alpha);
+        interpolation.rendered = trans.translation;
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn check_point_scored(
+    mut commands: Commands,
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    paused: Res<PongPaused>,
+    mut point_writers: PointWriters,
+    mut goal_writer: EventWriter<GoalEvent>,
+    mut bounce_writer: EventWriter<BounceEvent>,
+    mut games: Query<(Entity, &PongOptions, &mut BallSpeedupTimer, &mut TimeSinceGoal, &Overtime)>,
+    mut balls: Query<(
+        Entity, &Parent, &mut Transform, &mut Velocity, &mut LastTouchedBy, &mut ServeRamp, &mut BallSize,
+        &mut Sprite, &mut PaddleBounceCooldown, &mut WallBounceCooldown, &mut Interpolation, Option<&mut PendingReset>
+    ), IsBall>,
+    mut players: Query<(&Parent, &Player, &mut Transform, &mut Score, &mut Velocity, &mut Interpolation), IsPlayer>
+) {
+    // The balls don't move while the game is paused, but the goal delay would still run out.
+    if paused.0 {
+        return;
+    }
+    let delta = Duration::from_secs_f32(step.delta(&time));
+
+    for (game, options, mut speedup_timer, mut time_since_goal, overtime) in games.iter_mut() {
+        let max_x = options.game.size.x / 2.;
+        let min_x = -max_x;
+
+        // The heights of the paddles (0 is for player 1; 1 is for player 2) before they get recentered
+        // after a goal, see `ServeOrigin::ScorerPaddle`.
+        let mut paddle_heights = (0., 0.);
+        for (_, player, p_trans, ..) in players.iter().filter(|(parent, ..)| parent.0 == game) {
+            match player {
+                Player::Player1 => paddle_heights.0 = p_trans.translation.y,
+                Player::Player2 => paddle_heights.1 = p_trans.translation.y,
+            }
+        }
+
+        // The position and velocity of a ball, which gets served again.
+        let serve_ball = |serve: Serve, scorer: Option<Player>| -> (Vec3, Vec2) {
+            let mut position = Ball::start_position(options);
+            if let (ServeOrigin::ScorerPaddle, Some(scorer)) = (options.game.serve_origin, scorer) {
+                let height = match scorer {
+                    Player::Player1 => paddle_heights.0,
+                    Player::Player2 => paddle_heights.1,
+                };
+                position.y = Ball::clamp_y(options, height);
+            }
+            let velocity = match serve {
+                Serve::StartVelocity(server) => options.serve_velocity(server),
+                Serve::Stopped => Vec2::ZERO,
+            };
+            (position, velocity)
+        };
+        // Only the first point in the overtime wins, even if several balls score in the same step.
+        let mut golden_goal = overtime.0;
+        // Returns whether the game is over and the number of points played so far.
+        let mut reset_player_and_send_event = |scoring_player: Player| -> (bool, u16) {
+            let mut game_over = false;
+            let mut points_played = 0;
+            for (_, player, mut p_trans, mut score, mut p_vel, mut p_interpolation) in players.iter_mut()
+                .filter(|(parent, ..)| parent.0 == game) {
+                if *player == scoring_player {
+                    game_over = apply_point(options, game, *player, &mut score, golden_goal, &mut point_writers);
+                    golden_goal = false;
+                }
+                let recenter = match options.player.recenter_on_goal {
+                    RecenterMode::Both => true,
+                    RecenterMode::None => false,
+                    RecenterMode::ScorerOnly => *player == scoring_player,
+                    RecenterMode::ConcederOnly => *player != scoring_player,
+                };
+                if recenter {
+                    p_trans.translation.y = player.start_position(options).y;
+                    p_interpolation.snap(p_trans.translation);
+                }
+                if !options.player.keep_momentum_on_goal {
+                    p_vel.0 = Vec2::ZERO;
+                }
+                points_played += score.0;
+            }
+            (game_over, points_played)
+        };
+
+        // The player whose edge was reached by a ball and what happens there.
+        let edge_reached = |x: f32, hbsx: f32, vel: Vec2| -> Option<(Player, ScoreMode)> {
+            let defender = if x - hbsx <= min_x {
+                Player::Player1
+            } else if x + hbsx >= max_x {
+                Player::Player2
+            } else {
+                return None;
+            };
+            // The angle between the velocity and the goal line, the ball gets saved if it's too shallow.
+            let saved = match options.game.save_angle {
+                Some(save_angle) => vel.x.abs().atan2(vel.y.abs()) < save_angle,
+                None => false,
+            };
+            let mode = match options.game.score_mode {
+                _ if options.wall_for(&defender) => ScoreMode::Bounce,
+                ScoreMode::Goals if saved => ScoreMode::Bounce,
+                mode => mode,
+            };
+            Some((defender, mode))
+        };
+        // If several balls reach a goal in the same step, the one which is the furthest behind its
+        // goal line crossed it first.
+        let first_goal = match options.game.multi_goal_mode {
+            MultiGoalMode::AwardAll => None,
+            MultiGoalMode::FirstOnly => balls.iter()
+                .filter(|(_, parent, .., pending)| parent.0 == game && pending.is_none())
+                .filter_map(|(ball, _, trans, vel, _, _, size, ..)| {
+                    let hbsx = options.ball.shape.collision_size(size.0).x / 2.;
+                    match edge_reached(trans.translation.x, hbsx, vel.0) {
+                        Some((_, ScoreMode::Goals)) => Some((ball, trans.translation.x.abs() + hbsx)),
+                        _ => None,
+                    }
+                })
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(ball, _)| ball),
+        };
+
+        for (
+            ball, _, mut b_trans, mut vel, mut last_touched, mut ramp, mut size, mut sprite, mut paddle_cooldown,
+            mut wall_cooldown, mut interpolation, pending
+        ) in balls.iter_mut().filter(|(_, parent, ..)| parent.0 == game) {
+            // A ball which already scored can't score again until it was reset.
+            if let Some(mut pending) = pending {
+                if pending.timer.tick(delta).finished() {
+                    let (position, velocity) = serve_ball(pending.serve, Some(pending.scorer));
+                    reset_ball_state(
+                        options,
+                        (
+                            &mut b_trans, &mut vel, &mut last_touched, &mut ramp, &mut size, &mut sprite,
+                            &mut paddle_cooldown, &mut wall_cooldown, &mut interpolation
+                        ),
+                        position,
+                        velocity,
+                    );
+                    speedup_timer.reset();
+                    commands.entity(ball).remove::<PendingReset>();
+                }
+                continue;
+            }
+            let hbsx = options.ball.shape.collision_size(size.0).x / 2.;
+            let (defender, mode) = match edge_reached(b_trans.translation.x, hbsx, vel.0) {
+                Some(reached) => reached,
+                None => continue,
+            };
+
+            match (mode, defender) {
+                (ScoreMode::Goals, _) if first_goal.is_some_and(|first| first != ball) => {
+                    let (position, velocity) = serve_ball(Serve::StartVelocity(None), None);
+                    reset_ball_state(
+                        options,
+                        (
+                            &mut b_trans, &mut vel, &mut last_touched, &mut ramp, &mut size, &mut sprite,
+                            &mut paddle_cooldown, &mut wall_cooldown, &mut interpolation
+                        ),
+                        position,
+                        velocity,
+                    );
+                }
+                (ScoreMode::Goals, _) => {
+                    let scorer = match options.game.score_attribution {
+                        ScoreAttribution::ByGoalSide => defender.opponent(),
+                        ScoreAttribution::ByLastTouch => last_touched.0.unwrap_or_else(|| defender.opponent()),
+                    };
+                    goal_writer.send(GoalEvent { scorer, ball_pos: b_trans.translation, game });
+                    time_since_goal.0 = 0.;
+                    let (game_over, points_played) = reset_player_and_send_event(scorer);
+                    let serve = if game_over {
+                        Serve::Stopped
+                    } else {
+                        Serve::StartVelocity(options.server_after(points_played))
+                    };
+                    if options.game.goal_delay > 0. {
+                        commands.entity(ball).insert(PendingReset {
+                            timer: Timer::from_seconds(options.game.goal_delay, false),
+                            serve,
+                            scorer,
+                        });
+                    } else {
+                        // The speedup of the last rally is discarded, the next one starts from the base speed.
+                        let (position, velocity) = serve_ball(serve, Some(scorer));
+                        reset_ball_state(
+                            options,
+                            (
+                                &mut b_trans, &mut vel, &mut last_touched, &mut ramp, &mut size, &mut sprite,
+                                &mut paddle_cooldown, &mut wall_cooldown, &mut interpolation
+                            ),
+                            position,
+                            velocity,
+                        );
+                        speedup_timer.reset();
+                    }
+                }
+                // Checking the direction prevents the ball from wrapping back in the next frame.
+                (ScoreMode::WrapAround, Player::Player1) => if vel.0.x < 0. {
+                    b_trans.translation.x = max_x - hbsx;
+                    interpolation.snap(b_trans.translation);
+                },
+                (ScoreMode::WrapAround, Player::Player2) => if vel.0.x > 0. {
+                    b_trans.translation.x = min_x + hbsx;
+                    interpolation.snap(b_trans.translation);
+                },
+                (ScoreMode::Bounce, Player::Player1) => {
+                    vel.0.x = vel.0.x.abs();
+                    b_trans.translation.x = min_x + hbsx;
+                    bounce_writer.send(BounceEvent { ball, surface: BounceSurface::Wall, game });
+                }
+                (ScoreMode::Bounce, Player::Player2) => {
+                    vel.0.x = -vel.0.x.abs();
+                    b_trans.translation.x = max_x - hbsx;
+                    bounce_writer.send(BounceEvent { ball, surface: BounceSurface::Wall, game });
+                }
+            }
+        }
+    }
+}
+
+fn update_score_text(
+    mut event_reader: EventReader<ScoredPointEvent>,
+    mut match_point_reader: EventReader<MatchPointEvent>,
+    games: Query<(Entity, &PongOptions, &Overtime, ChangeTrackers<Overtime>)>,
+    mut score_text: Query<(&Parent, &mut Text), With<ScoreDisplayText>>,
+) {
+    for ScoredPointEvent(player, Score(points), game) in event_reader.iter() {
+        let options = match games.get(*game) {
+            Ok((_, options, ..)) => options,
+            Err(_) => continue,
+        };
+        for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == *game) {
+            match player {
+                Player::Player1 => text.sections[0].value = format!("{}", options.displayed_score(*points)),
+                Player::Player2 => text.sections[2].value = format!("{}", options.displayed_score(*points)),
+            }
+        }
+    }
+    for MatchPointEvent { player, game } in match_point_reader.iter() {
+        let color = match games.get(*game).map(|(_, options, ..)| &options.score_display_options) {
+            Ok(Some(ScoreDisplayOptions { match_point_color: Some(color), .. })) => *color,
+            _ => continue,
+        };
+        for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == *game) {
+            match player {
+                Player::Player1 => text.sections[0].style.color = color,
+                Player::Player2 => text.sections[2].style.color = color,
+            }
+        }
+    }
+    for (game, options, overtime, overtime_tracker) in games.iter() {
+        let score_options = match &options.score_display_options {
+            Some(score_options) => score_options,
+            None => continue,
+        };
+        if let Some(color) = score_options.overtime_color {
+            if !overtime_tracker.is_changed() {
+                continue;
+            }
+            for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                text.sections[1].style.color = if overtime.0 { color } else { score_options.font_color };
+            }
+        }
+    }
+}
+
+/// Pops up the size of a score text after a point and shrinks it back to the font size.
+fn animate_score_text(
+    time: Res<Time>,
+    // The remaining animation time of the scores of each game (0 is for player 1; 1 is for player 2).
+    mut remaining: Local<HashMap<Entity, [f32; 2]>>,
+    mut event_reader: EventReader<ScoredPointEvent>,
+    games: Query<(Entity, &PongOptions)>,
+    mut score_text: Query<(&Parent, &mut Text), With<ScoreDisplayText>>,
+) {
+    let events: Vec<&ScoredPointEvent> = event_reader.iter().collect();
+    for (game, options) in games.iter() {
+        let score_options = match &options.score_display_options {
+            Some(score_options) if score_options.score_animation => score_options,
+            _ => continue,
+        };
+
+        let remaining = remaining.entry(game).or_default();
+        for ScoredPointEvent(player, ..) in events.iter().filter(|event| event.game() == game) {
+            match player {
+                Player::Player1 => remaining[0] = SCORE_ANIMATION_DURATION,
+                Player::Player2 => remaining[1] = SCORE_ANIMATION_DURATION,
+            }
+        }
+        for (i, section) in [0, 2].into_iter().enumerate() {
+            if remaining[i] <= 0. {
+                continue;
+            }
+            remaining[i] = (remaining[i] - time.delta_seconds()).max(0.);
+            let progress = remaining[i] / SCORE_ANIMATION_DURATION;
+            for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                text.sections[section].style.font_size =
+                    score_options.font_size * (1. + (SCORE_ANIMATION_SCALE - 1.) * progress);
+            }
+        }
+    }
+}
+
+/// Bounces the score of the winner and dims the score of the loser until the game gets reset.
+fn celebrate_win(
+    time: Res<Time>,
+    // The winner of each game which is over and the time since it's over.
+    mut celebrations: Local<HashMap<Entity, (Player, f32)>>,
+    mut game_over_reader: EventReader<GameOverEvent>,
+    mut reset_reader: EventReader<ResetGameEvent>,
+    games: Query<(Entity, &PongOptions)>,
+    mut score_text: Query<(&Parent, &mut Text), With<ScoreDisplayText>>,
+) {
+    let section = |player: &Player| match player {
+        Player::Player1 => 0,
+        Player::Player2 => 2,
+    };
+    let game_overs: Vec<&GameOverEvent> = game_over_reader.iter().collect();
+    let resets: Vec<&ResetGameEvent> = reset_reader.iter().collect();
+
+    for (game, options) in games.iter() {
+        let score_options = match &options.score_display_options {
+            Some(score_options) if score_options.win_celebration => score_options,
+            _ => continue,
+        };
+
+        if resets.iter().any(|ResetGameEvent(target)| targets(*target, game)) && celebrations.remove(&game).is_some() {
+            for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                for player in [Player::Player1, Player::Player2] {
+                    text.sections[section(&player)].style.font_size = score_options.font_size;
+                    text.sections[section(&player)].style.color = options.score_color_for(&player);
+                }
+            }
+        }
+        if let Some(GameOverEvent { winner, .. }) = game_overs.iter().rev().find(|event| event.game == game) {
+            celebrations.insert(game, (*winner, 0.));
+            let loser = winner.opponent();
+            for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == game) {
+                text.sections[section(&loser)].style.color.set_a(WIN_CELEBRATION_DIM);
+            }
+        }
+        let (winner, elapsed) = match celebrations.get_mut(&game) {
+            Some(celebration) => celebration,
+            None => continue,
+        };
+
+        *elapsed += time.delta_seconds();
+        let bounce = (*elapsed * WIN_CELEBRATION_FREQUENCY * std::f32::consts::PI).sin().abs();
+        for (_, mut text) in score_text.iter_mut().filter(|(parent, _)| parent.0 == game) {
+            text.sections[section(winner)].style.font_size =
+                score_options.font_size * (1. + (SCORE_ANIMATION_SCALE - 1.) * bounce);
+        }
+    }
+}
+
+fn update_ball_speed_text(
+    balls: Query<(&Parent, &Velocity), IsBall>,
+    mut speed_texts: Query<(&Parent, &mut Text), With<BallSpeedText>>,
+) {
+    for (Parent(game), mut text) in speed_texts.iter_mut() {
+        let speed = balls.iter()
+            .filter(|(parent, _)| parent.0 == *game)
+            .map(|(_, vel)| vel.0.length())
+            .fold(0., f32::max);
+        text.sections[0].value = format!("{:.0}", speed);
+    }
+}
+
+/// Respawns the digits of every changed score, so the images also follow scores which were
+/// changed without a `ScoredPointEvent` (e.g. by a reset).
+fn update_score_images(
+    mut commands: Commands,
+    games: Query<&PongOptions>,
+    scores: Query<(&Parent, &Player, &Score), Changed<Score>>,
+    score_digits: Query<(Entity, &Parent, &ScoreDigit)>,
+) {
+    for (Parent(game), player, Score(points)) in scores.iter() {
+        let options = match games.get(*game) {
+            Ok(options) => options,
+            Err(_) => continue,
+        };
+        let (atlas, digit_size) = match options.score_display_options.as_ref().map(|o| &o.display) {
+            Some(ScoreDisplay::Images { atlas, digit_size }) => (atlas, *digit_size),
+            _ => continue,
+        };
+
+        for (entity, parent, ScoreDigit(digit_player)) in score_digits.iter() {
+            if parent.0 == *game && digit_player == player {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        commands.entity(*game).with_children(|parent| {
+            spawn_score_digits(parent, options, atlas, digit_size, *player, *points);
+        });
+    }
+}
+
+fn sync_pong_state(
+    mut games: Query<(Entity, &mut PongState)>,
+    balls: Query<(&Parent, &Transform, &Velocity), IsBall>,
+    players: Query<(&Parent, &Player, &Transform, &Score), IsPlayer>,
+) {
+    for (game, mut state) in games.iter_mut() {
+        state.balls.clear();
+        for (_, trans, vel) in balls.iter().filter(|(parent, ..)| parent.0 == game) {
+            state.balls.push(BallState { position: trans.translation, velocity: vel.0 });
+        }
+        for (_, player, trans, score) in players.iter().filter(|(parent, ..)| parent.0 == game) {
+            match player {
+                Player::Player1 => {
+                    state.scores.0 = score.0;
+                    state.paddles.0 = trans.translation;
+                }
+                Player::Player2 => {
+                    state.scores.1 = score.0;
+                    state.paddles.1 = trans.translation;
+                }
+            }
+        }
+    }
+}
+
+fn tick_time_since_goal(
+    time: Res<Time>,
+    paused: Res<PongPaused>,
+    mut games: Query<&mut TimeSinceGoal>,
+) {
+    if paused.0 {
+        return;
+    }
+    for mut time_since_goal in games.iter_mut() {
+        time_since_goal.0 += time.delta_seconds();
+    }
+}
+
+fn collect_stats(
+    mut bounce_reader: EventReader<BounceEvent>,
+    mut scored_reader: EventReader<ScoredPointEvent>,
+    mut reset_reader: EventReader<ResetGameEvent>,
+    mut games: Query<(Entity, &mut PongStats, &mut TimeSinceGoal)>,
+    balls: Query<(&Parent, &Velocity), IsBall>,
+) {
+    let bounces: Vec<&BounceEvent> = bounce_reader.iter().collect();
+    let points: Vec<&ScoredPointEvent> = scored_reader.iter().collect();
+    let resets: Vec<&ResetGameEvent> = reset_reader.iter().collect();
+
+    for (game, mut stats, mut time_since_goal) in games.iter_mut() {
+        if resets.iter().any(|ResetGameEvent(target)| targets(*target, game)) {
+            *stats = PongStats::default();
+            time_since_goal.0 = 0.;
+        }
+
+        for BounceEvent { surface, .. } in bounces.iter().filter(|event| event.game == game) {
+            stats.bounces += 1;
+            if let BounceSurface::Paddle(_) = surface {
+                stats.rally += 1;
+                stats.longest_rally = stats.longest_rally.max(stats.rally);
+            }
+        }
+        for ScoredPointEvent(player, ..) in points.iter().filter(|event| event.game() == game) {
+            match player {
+                Player::Player1 => stats.points.0 += 1,
+                Player::Player2 => stats.points.1 += 1,
+            }
+            stats.rally = 0;
+        }
+        for (_, vel) in balls.iter().filter(|(parent, _)| parent.0 == game) {
+            let speed = vel.0.length();
+            if speed > stats.max_ball_speed {
+                stats.max_ball_speed = speed;
+            }
+        }
+    }
+}
+
+fn sync_paddle_positions(
+    mut games: Query<(Entity, &mut PaddlePositions)>,
+    players: Query<(&Parent, &Player, &Transform), IsPlayer>,
+) {
+    for (game, mut positions) in games.iter_mut() {
+        for (_, player, trans) in players.iter().filter(|(parent, ..)| parent.0 == game) {
+            match player {
+                Player::Player1 => positions.p1 = trans.translation.y,
+                Player::Player2 => positions.p2 = trans.translation.y,
+            }
+        }
+    }
+}
+
+fn run_callbacks(
+    mut callbacks: ResMut<PongCallbacks>,
+    mut scored_reader: EventReader<ScoredPointEvent>,
+    mut bounce_reader: EventReader<BounceEvent>,
+) {
+    for ScoredPointEvent(player, Score(points), _) in scored_reader.iter() {
+        for callback in callbacks.on_score.iter_mut() {
+            callback(*player, *points);
+        }
+    }
+    for event in bounce_reader.iter() {
+        for callback in callbacks.on_bounce.iter_mut() {
+            callback(event);
+        }
+    }
+}
+
+fn spawn_goal_particles(
+    mut commands: Commands,
+    mut goal_reader: EventReader<GoalEvent>,
+    games: Query<&PongOptions>,
+) {
+    for GoalEvent { scorer, ball_pos, game } in goal_reader.iter() {
+        let options = match games.get(*game) {
+            Ok(options) if options.game.goal_particles => options,
+            _ => continue,
+        };
+
+        // The goal of player 2 is on the right, the particles always fly into the court.
+        let side = match scorer {
+            Player::Player1 => 1.,
+            Player::Player2 => -1.,
+        };
+        let position = Vec3::new(side * options.game.size.x / 2., ball_pos.y, ball_pos.z);
+        commands.entity(*game).with_children(|parent| {
+            for i in 0..GOAL_PARTICLE_COUNT {
+                let angle = std::f32::consts::PI * (i as f32 + 0.5) / GOAL_PARTICLE_COUNT as f32;
+                let direction = Vec2::new(-side * angle.sin(), angle.cos());
+                parent.spawn()
+                    .insert(GoalParticle {
+                        velocity: direction * GOAL_PARTICLE_SPEED,
+                        lifetime: Timer::from_seconds(GOAL_PARTICLE_LIFETIME, false),
+                    })
+                    .insert_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: options.ball.color,
+                            custom_size: Some(Vec2::splat(GOAL_PARTICLE_SIZE)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(position),
+                        ..Default::default()
+                    });
+            }
+        });
+    }
+}
+
+/// Tints the background in the color of the scorer and reverts it once the flash is over. Without
+/// a background sprite (`Background::None`) there is nothing to flash.
+fn flash_goal(
+    time: Res<Time>,
+    mut flashes: Local<HashMap<Entity, Timer>>,
+    mut scored_reader: EventReader<ScoredPointEvent>,
+    mut backgrounds: Query<(Entity, &PongOptions, &mut Sprite), With<PongGame>>,
+) {
+    let points: Vec<&ScoredPointEvent> = scored_reader.iter().collect();
+    for (game, options, mut sprite) in backgrounds.iter_mut() {
+        if !options.game.goal_flash {
+            continue;
+        }
+
+        if let Some(ScoredPointEvent(player, ..)) = points.iter().rev().find(|event| event.game() == game) {
+            sprite.color = options.color_for(player);
+            flashes.insert(game, Timer::from_seconds(GOAL_FLASH_DURATION, false));
+        } else if let Some(timer) = flashes.get_mut(&game) {
+            if timer.tick(time.delta()).finished() {
+                // A textured background is drawn untinted, i.e. white.
+                sprite.color = match options.game.background {
+                    Background::Color(color) => color,
+                    _ => Color::WHITE,
+                };
+                flashes.remove(&game);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn spawn_ball_shadows(
+    mut commands: Commands,
+    games: Query<&PongOptions>,
+    balls: Query<(Entity, &Parent, &Transform, &BallSize), (Added<Ball>, Without<Player>)>,
+) {
+    for (ball, Parent(game), trans, size) in balls.iter() {
+        let shadow = match games.get(*game).map(|options| options.game.ball_shadow) {
+            Ok(Some(shadow)) => shadow,
+            _ => continue,
+        };
+
+        commands.entity(*game).with_children(|parent| {
+            parent.spawn()
+                .insert(BallShadow(ball))
+                .insert_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: shadow.color,
+                        custom_size: Some(size.0),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(
+                        trans.translation + shadow.offset.extend(-0.5)
+                    ),
+                    ..Default::default()
+                });
+        });
+    }
+}
+
+/// Moves the shadows with their balls (after the interpolation), the shadows of despawned balls
+/// are despawned too.
+fn update_ball_shadows(
+    mut commands: Commands,
+    games: Query<&PongOptions>,
+    balls: Query<(&Transform, &BallSize), IsBall>,
+    mut shadows: Query<(Entity, &Parent, &BallShadow, &mut Transform, &mut Sprite), Without<Ball>>,
+) {
+    for (entity, Parent(game), shadow, mut trans, mut sprite) in shadows.iter_mut() {
+        let offset = match games.get(*game).map(|options| options.game.ball_shadow) {
+            Ok(Some(shadow)) => shadow.offset,
+            _ => continue,
+        };
+        match balls.get(shadow.0) {
+            Ok((ball_trans, size)) => {
+                trans.translation = ball_trans.translation + offset.extend(-0.5);
+                sprite.custom_size = Some(size.0);
+            }
+            Err(_) => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+/// A rectangle of the given size with rounded corners, built as triangle fan around its center.
+fn rounded_rect(size: Vec2, radius: f32) -> Mesh {
+    let half = size / 2.;
+    let radius = radius.min(half.x).min(half.y).max(0.);
+    let mut positions = vec![[0., 0., 0.]];
+    // Counterclockwise, starting with the top right corner.
+    for (corner, sign) in [(1., 1.), (-1., 1.), (-1., -1.), (1., -1.)].into_iter().enumerate() {
+        let center = Vec2::new(sign.0 * (half.x - radius), sign.1 * (half.y - radius));
+        for segment in 0..=CORNER_SEGMENTS {
+            let angle = (corner as f32 + segment as f32 / CORNER_SEGMENTS as f32) * std::f32::consts::FRAC_PI_2;
+            let point = center + radius * Vec2::new(angle.cos(), angle.sin());
+            positions.push([point.x, point.y, 0.]);
+        }
+    }
+    let count = positions.len() as u32;
+    let indices = (1..count).flat_map(|i| [0, i, if i + 1 < count { i + 1 } else { 1 }]).collect();
+    let normals = vec![[0., 0., 1.]; positions.len()];
+    let uvs: Vec<[f32; 2]> = positions.iter()
+        .map(|[x, y, _]| [x / size.x.max(f32::EPSILON) + 0.5, 0.5 - y / size.y.max(f32::EPSILON)])
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh
+}
+
+/// Renders the balls and paddles with a corner radius as rounded mesh with the size and color of
+/// their sprite, which gets hidden meanwhile. Without the mesh assets (e.g. in a headless app)
+/// nothing happens.
+#[allow(clippy::type_complexity)]
+fn round_corners(
+    mut commands: Commands,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<ColorMaterial>>>,
+    games: Query<&PongOptions>,
+    mut bodies: Query<
+        (Entity, &Parent, Option<&Ball>, &Sprite, &mut Visibility, Option<&Children>),
+        Or<(With<Ball>, With<Player>)>
+    >,
+    mut rounded: Query<(&mut RoundedCorners, &mut Mesh2dHandle, &Handle<ColorMaterial>)>,
+) {
+    let (mut meshes, mut materials) = match (meshes, materials) {
+        (Some(meshes), Some(materials)) => (meshes, materials),
+        _ => return,
+    };
+    for (entity, Parent(game), ball, sprite, mut visibility, children) in bodies.iter_mut() {
+        let options = match games.get(*game) {
+            Ok(options) => options,
+            Err(_) => continue,
+        };
+        let radius = if ball.is_some() { options.ball.corner_radius } else { options.player.corner_radius };
+        let size = sprite.custom_size.unwrap_or(Vec2::ONE);
+        let mesh_entity = children.and_then(|children| children.iter().copied().find(|child| rounded.get(*child).is_ok()));
+
+        match mesh_entity {
+            Some(mesh_entity) if radius <= 0. => {
+                commands.entity(mesh_entity).despawn_recursive();
+                visibility.is_visible = true;
+            }
+            Some(mesh_entity) => {
+                let (mut corners, mut mesh, material) = rounded.get_mut(mesh_entity).unwrap();
+                if corners.size != size || corners.radius != radius {
+                    *corners = RoundedCorners { size, radius };
+                    mesh.0 = meshes.add(rounded_rect(size, radius));
+                }
+                // Only touched on a change, so the material doesn't get prepared again every frame.
+                if materials.get(material).is_some_and(|material| material.color != sprite.color) {
+                    if let Some(material) = materials.get_mut(material) {
+                        material.color = sprite.color;
+                    }
+                }
+            }
+            None if radius > 0. => {
+                visibility.is_visible = false;
+                let mesh_entity = commands.spawn_bundle(MaterialMesh2dBundle {
+                        mesh: meshes.add(rounded_rect(size, radius)).into(),
+                        material: materials.add(sprite.color.into()),
+                        ..Default::default()
+                    })
+                    .insert(RoundedCorners { size, radius })
+                    .id();
+                commands.entity(entity).push_children(&[mesh_entity]);
+            }
+            None => {}
+        }
+    }
+}
+
+fn update_impact_marks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut marks: Query<(Entity, &mut ImpactMark, &mut Sprite)>,
+) {
+    for (entity, mut mark, mut sprite) in marks.iter_mut() {
+        if mark.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        sprite.color.set_a(1. - mark.0.percent());
+    }
+}
+
+fn update_goal_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut GoalParticle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut trans, mut sprite) in particles.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        trans.translation += particle.velocity.extend(0.) * delta;
+        sprite.color.set_a(1. - particle.lifetime.percent());
+    }
+}
+
+fn handle_game_over(
+    time: Res<Time>,
+    key_input: Res<Input<KeyCode>>,
+    paused: Res<PongPaused>,
+    mut game_over_reader: EventReader<GameOverEvent>,
+    mut draw_reader: EventReader<DrawEvent>,
+    mut reset_writer: EventWriter<ResetGameEvent>,
+    mut games: Query<(Entity, &PongOptions, &mut GameOverState)>,
+) {
+    let ended: Vec<Entity> = game_over_reader.iter().map(|event| event.game)
+        .chain(draw_reader.iter().map(|event| event.game))
+        .collect();
+
+    for (game, options, mut state) in games.iter_mut() {
+        if ended.contains(&game) && !state.over {
+            state.over = true;
+            state.restart_timer = match options.game.after_game_over {
+                AfterGameOver::AutoRestart { delay } => Some(Timer::from_seconds(delay, false)),
+                _ => None,
+            };
+            continue;
+        }
+        if !state.over {
+            continue;
+        }
+
+        let restart = match options.game.after_game_over {
+            AfterGameOver::Freeze => false,
+            AfterGameOver::AutoRestart { .. } => match &mut state.restart_timer {
+                // The delay doesn't run out while the game is paused.
+                Some(_) if paused.0 => false,
+                Some(timer) => timer.tick(time.delta()).finished(),
+                None => true,
+            },
+            AfterGameOver::WaitForKey { key } => key_input.just_pressed(key),
+        };
+        if restart {
+            reset_writer.send(ResetGameEvent(Some(game)));
+        }
+    }
+}
+
+fn track_streaks(
+    mut scored_reader: EventReader<ScoredPointEvent>,
+    mut reset_reader: EventReader<ResetGameEvent>,
+    mut streak_writer: EventWriter<StreakEvent>,
+    mut games: Query<(Entity, &PongOptions, &mut Streak)>,
+    mut paddles: Query<(&Parent, &Player, &mut Sprite), IsPlayer>,
+) {
+    let points: Vec<&ScoredPointEvent> = scored_reader.iter().collect();
+    let resets: Vec<&ResetGameEvent> = reset_reader.iter().collect();
+
+    for (game, options, mut streak) in games.iter_mut() {
+        let threshold = match options.game.streak_threshold {
+            Some(threshold) => threshold,
+            None => continue,
+        };
+
+        if resets.iter().any(|ResetGameEvent(target)| targets(*target, game)) {
+            *streak = Streak::default();
+        }
+        for ScoredPointEvent(player, ..) in points.iter().filter(|event| event.game() == game) {
+            if streak.player == Some(*player) {
+                streak.count += 1;
+            } else {
+                streak.player = Some(*player);
+                streak.count = 1;
+            }
+            if streak.count >= threshold {
+                streak_writer.send(StreakEvent { player: *player, streak: streak.count, game });
+            }
+        }
+
+        if let Some(streak_color) = options.game.streak_color {
+            if !streak.is_changed() {
+                continue;
+            }
+            for (_, player, mut sprite) in paddles.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+                sprite.color = if streak.player == Some(*player) && streak.count >= threshold {
+                    streak_color
+                } else {
+                    options.color_for(player)
+                };
+            }
+        }
+    }
+}
+
+/// Ends a game when the time is up, or starts the overtime in case of a sudden death (the golden
+/// goal itself is handled by `check_point_scored`).
+#[allow(clippy::type_complexity)]
+fn tick_match_timer(
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    paused: Res<PongPaused>,
+    mut game_over_writer: EventWriter<GameOverEvent>,
+    mut draw_writer: EventWriter<DrawEvent>,
+    mut games: Query<(Entity, &PongOptions, &GameOverState, &AwaitingFirstServe, &mut MatchTimer, &mut Overtime)>,
+    players: Query<(&Parent, &Player, &Score), IsPlayer>,
+) {
+    if paused.0 {
+        return;
+    }
+    for (game, options, game_over, awaiting, mut match_timer, mut overtime) in games.iter_mut() {
+        if game_over.over || awaiting.0 || overtime.0 {
+            continue;
+        }
+        if !match_timer.0.tick(Duration::from_secs_f32(step.delta(&time))).just_finished() {
+            continue;
+        }
+
+        match leader(scores_of(game, players.iter())) {
+            Some(winner) => game_over_writer.send(GameOverEvent { winner, game }),
+            None if options.game.sudden_death => overtime.0 = true,
+            None => draw_writer.send(DrawEvent { game }),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn reset_game(
+    mut commands: Commands,
+    mut reset_reader: EventReader<ResetGameEvent>,
+    mut games: Query<(
+        Entity, &PongOptions, &mut GameOverState, &mut BallSpeedupTimer, &mut MatchElapsed, &mut AwaitingFirstServe,
+        Option<&mut MatchTimer>, &mut Overtime,
+    )>,
+    mut balls: ServedBalls,
+    mut players: Query<(&Parent, &Player, &mut Transform, &mut Score, &mut Velocity), IsPlayer>,
+    mut score_texts: Query<(&Parent, &mut Text), With<ScoreDisplayText>>,
+) {
+    let resets: Vec<&ResetGameEvent> = reset_reader.iter().collect();
+    if resets.is_empty() {
+        return;
+    }
+
+    for (
+        game, options, mut game_over, mut speedup_timer, mut elapsed, mut awaiting, match_timer, mut overtime
+    ) in games.iter_mut() {
+        if !resets.iter().any(|ResetGameEvent(target)| targets(*target, game)) {
+            continue;
+        }
+
+        game_over.over = false;
+        game_over.restart_timer = None;
+        speedup_timer.0.reset();
+        elapsed.0 = 0.;
+        awaiting.0 = options.game.wait_for_ready;
+        if let Some(mut match_timer) = match_timer {
+            match_timer.0.reset();
+        }
+        overtime.0 = false;
+
+        // Only a single ball remains, like at the start of the game.
+        for (i, (
+            entity, _, mut trans, mut vel, mut last_touched, mut ramp, mut size, mut sprite, mut paddle_cooldown,
+            mut wall_cooldown, mut interpolation
+        )) in balls.iter_mut().filter(|(_, parent, ..)| parent.0 == game).enumerate() {
+            if i > 0 {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+            let velocity = if options.game.wait_for_ready {
+                Vec2::ZERO
+            } else {
+                options.serve_velocity(options.server_after(0))
+            };
+            reset_ball_state(
+                options,
+                (
+                    &mut trans, &mut vel, &mut last_touched, &mut ramp, &mut size, &mut sprite, &mut paddle_cooldown,
+                    &mut wall_cooldown, &mut interpolation
+                ),
+                Ball::start_position(options),
+                velocity,
+            );
+            commands.entity(entity).remove::<PendingReset>();
+        }
+        for (_, player, mut trans, mut score, mut vel) in players.iter_mut().filter(|(parent, ..)| parent.0 == game) {
+            trans.translation = player.start_position(options);
+            score.0 = 0;
+            vel.0 = Vec2::ZERO;
+        }
+        for (_, mut text) in score_texts.iter_mut().filter(|(parent, _)| parent.0 == game) {
+            for (i, player) in [(0, Player::Player1), (2, Player::Player2)] {
+                text.sections[i].value = format!("{}", options.displayed_score(0));
+                text.sections[i].style.color = options.score_color_for(&player);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn escalate_rally(
+    time: Res<Time>,
+    step: Res<PhysicsStep>,
+    freeze: Res<FreezeBall>,
+    paused: Res<PongPaused>,
+    mut rally_times: Local<HashMap<Entity, f32>>,
+    mut goal_reader: EventReader<GoalEvent>,
+    mut reset_reader: EventReader<ResetGameEvent>,
+    mut spawn_writer: EventWriter<SpawnBallEvent>,
+    games: Query<(Entity, &PongOptions, &GameOverState, &AwaitingFirstServe)>,
+) {
+    let goals: Vec<Entity> = goal_reader.iter().map(|event| event.game).collect();
+    let resets: Vec<&ResetGameEvent> = reset_reader.iter().collect();
+
+    for (game, options, game_over, awaiting) in games.iter() {
+        let escalation = match options.game.escalation {
+            Some(escalation) => escalation,
+            None => continue,
+        };
+        let rally_time = rally_times.entry(game).or_default();
+        if goals.contains(&game) || resets.iter().any(|ResetGameEvent(target)| targets(*target, game)) {
+            *rally_time = 0.;
+            continue;
+        }
+        if freeze.0 || paused.0 || game_over.over || awaiting.0 {
+            continue;
+        }
+
+        *rally_time += step.delta(&time);
+        if *rally_time >= escalation {
+            *rally_time -= escalation;
+            spawn_writer.send(SpawnBallEvent { position: None, velocity: None, game: Some(game) });
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+fn play_paddle_sounds(
+    sounds: Option<Res<SoundOptions>>,
+    audio: Res<Audio>,
+    mut bounce_reader: EventReader<BounceEvent>,
+) {
+    let sounds = match sounds {
+        Some(sounds) => sounds,
+        None => return,
+    };
+
+    for event in bounce_reader.iter() {
+        let sound = match event.surface {
+            BounceSurface::Paddle(Player::Player1) => &sounds.paddle_sounds.0,
+            BounceSurface::Paddle(Player::Player2) => &sounds.paddle_sounds.1,
+            BounceSurface::Wall => &None,
+        };
+        if let Some(sound) = sound {
+            audio.play(sound.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
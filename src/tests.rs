@@ -0,0 +1,1102 @@
+use super::*;
+use bevy::app::Events;
+use bevy::asset::AssetPlugin;
+use bevy::input::{keyboard::KeyboardInput, ElementState, InputPlugin};
+use bevy::window::{WindowId, WindowPlugin};
+
+/// The fixed physics step of the test games.
+const STEP: f32 = 1. / 60.;
+
+/// A headless game with the given options, in which the physics run once per update with a fixed
+/// step. The startup systems already ran.
+fn app(options: PongOptions) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(WindowPlugin::default())
+        .insert_resource(options)
+        .add_plugin(PongPlugin::new());
+    app.world.insert_resource(PhysicsStep(Some(STEP)));
+    app.update();
+    app
+}
+
+/// The game spawned by the plugin.
+fn pong_game(app: &mut App) -> Entity {
+    app.world.query_filtered::<Entity, With<PongGame>>().iter(&app.world).next().unwrap()
+}
+
+fn game_over(app: &mut App) -> bool {
+    let game = pong_game(app);
+    app.world.get::<GameOverState>(game).unwrap().over
+}
+
+fn balls(app: &mut App) -> Vec<Entity> {
+    app.world.query_filtered::<Entity, IsBall>().iter(&app.world).collect()
+}
+
+fn set_ball(app: &mut App, ball: Entity, position: Vec2, velocity: Vec2) {
+    app.world.get_mut::<Transform>(ball).unwrap().translation = position.extend(1.);
+    app.world.get_mut::<Velocity>(ball).unwrap().0 = velocity;
+}
+
+fn position(app: &App, entity: Entity) -> Vec3 {
+    app.world.get::<Transform>(entity).unwrap().translation
+}
+
+fn velocity(app: &App, entity: Entity) -> Vec2 {
+    app.world.get::<Velocity>(entity).unwrap().0
+}
+
+fn paddle(app: &mut App, player: Player) -> Entity {
+    app.world.query_filtered::<(Entity, &Player), IsPlayer>()
+        .iter(&app.world)
+        .find(|(_, p)| **p == player)
+        .map(|(entity, _)| entity)
+        .unwrap()
+}
+
+/// Runs the given number of updates and returns the surfaces of all bounces in between.
+fn bounces_during(app: &mut App, updates: usize) -> Vec<BounceSurface> {
+    let mut reader = app.world.get_resource::<Events<BounceEvent>>().unwrap().get_reader();
+    let mut surfaces = Vec::new();
+    for _ in 0..updates {
+        app.update();
+        let events = app.world.get_resource::<Events<BounceEvent>>().unwrap();
+        surfaces.extend(reader.iter(events).map(|event| event.surface));
+    }
+    surfaces
+}
+
+#[test]
+fn ball_inside_paddle_bounces_only_once() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    let paddle_x = position(&app, paddle).x;
+    // Overlapping the front of the paddle (but clear of the goal line) and slow, so flipping the
+    // velocity alone would leave it inside.
+    set_ball(&mut app, ball, Vec2::new(paddle_x + 6., 0.), Vec2::new(-60., 0.));
+
+    let mut last_x = f32::MIN;
+    let mut reader = app.world.get_resource::<Events<BounceEvent>>().unwrap().get_reader();
+    let mut surfaces = Vec::new();
+    for _ in 0..10 {
+        app.update();
+        let events = app.world.get_resource::<Events<BounceEvent>>().unwrap();
+        surfaces.extend(reader.iter(events).map(|event| event.surface));
+        assert!(velocity(&app, ball).x > 0.);
+        assert!(position(&app, ball).x > last_x);
+        last_x = position(&app, ball).x;
+    }
+    assert_eq!(surfaces, vec![BounceSurface::Paddle(Player::Player1)]);
+    assert_eq!(score(&mut app, Player::Player1), 0);
+    assert_eq!(score(&mut app, Player::Player2), 0);
+}
+
+#[test]
+fn fast_ball_does_not_pass_through_the_paddle() {
+    // Within one step the ball moves onto the middle of the paddle, which is thinner than the
+    // ball, or right through it.
+    for speed in [720., 3000.] {
+        let mut app = app(PongOptions::default());
+        let ball = balls(&mut app)[0];
+        let paddle = paddle(&mut app, Player::Player1);
+        let paddle_x = position(&app, paddle).x;
+        set_ball(&mut app, ball, Vec2::new(paddle_x + 12., 0.), Vec2::new(-speed, 0.));
+
+        assert_eq!(bounces_during(&mut app, 3), vec![BounceSurface::Paddle(Player::Player1)]);
+        assert!(velocity(&app, ball).x > 0.);
+        assert!(position(&app, ball).x > paddle_x);
+        assert_eq!(score(&mut app, Player::Player1), 0);
+        assert_eq!(score(&mut app, Player::Player2), 0);
+    }
+}
+
+#[test]
+fn ball_is_separated_from_paddle_after_bounce() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player2);
+    let paddle_x = position(&app, paddle).x;
+    set_ball(&mut app, ball, Vec2::new(paddle_x - 12., 0.), Vec2::new(300., 0.));
+
+    app.update();
+    let options = PongOptions::default();
+    let touching = (options.player.size.x + options.ball.size.x) / 2.;
+    assert!(velocity(&app, ball).x < 0.);
+    assert!(position(&app, ball).x <= paddle_x - touching);
+}
+
+fn last_touched(app: &App, ball: Entity) -> Option<Player> {
+    app.world.get::<LastTouchedBy>(ball).unwrap().0
+}
+
+#[test]
+fn last_touched_by_follows_paddle_hits() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    assert!(last_touched(&app, ball).is_none());
+
+    let paddle1 = paddle(&mut app, Player::Player1);
+    let paddle1_x = position(&app, paddle1).x;
+    set_ball(&mut app, ball, Vec2::new(paddle1_x + 12., 0.), Vec2::new(-300., 0.));
+    app.update();
+    assert!(last_touched(&app, ball) == Some(Player::Player1));
+
+    let paddle2 = paddle(&mut app, Player::Player2);
+    let paddle2_x = position(&app, paddle2).x;
+    set_ball(&mut app, ball, Vec2::new(paddle2_x - 12., 0.), Vec2::new(300., 0.));
+    app.world.get_mut::<PaddleBounceCooldown>(ball).unwrap().0 = 0;
+    app.update();
+    assert!(last_touched(&app, ball) == Some(Player::Player2));
+}
+
+#[test]
+fn last_touched_by_is_reset_on_serve() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    app.world.get_mut::<LastTouchedBy>(ball).unwrap().0 = Some(Player::Player1);
+    // Right behind the goal line of player 2.
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    assert!(last_touched(&app, ball).is_none());
+}
+
+#[test]
+fn resizing_the_game_updates_background_and_paddles() {
+    let mut app = app(PongOptions::default());
+    app.world.get_resource_mut::<PongOptions>().unwrap().game.size = Vec2::new(800., 300.);
+    app.update();
+
+    let background = app.world.query_filtered::<&Sprite, With<PongGame>>().iter(&app.world).next().unwrap();
+    assert_eq!(background.custom_size, Some(Vec2::new(800., 300.)));
+    let paddle = paddle(&mut app, Player::Player2);
+    let options = app.world.get_resource::<PongOptions>().unwrap();
+    assert_eq!(position(&app, paddle).x, 400. - options.player.size.x);
+}
+
+/// Runs an update after a short real time, so the systems using the frame time see a delta.
+fn update_after_frame(app: &mut App) {
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    app.update();
+}
+
+#[test]
+fn frozen_ball_stays_while_paddles_move() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    app.world.insert_resource(FreezeBall(true));
+    let ball_start = position(&app, ball);
+    let paddle_start = position(&app, paddle);
+
+    app.world.get_resource_mut::<Input<KeyCode>>().unwrap().press(KeyCode::W);
+    for _ in 0..3 {
+        update_after_frame(&mut app);
+    }
+    assert_eq!(position(&app, ball), ball_start);
+    assert!(position(&app, paddle).y > paddle_start.y);
+
+    app.world.insert_resource(FreezeBall(false));
+    app.update();
+    assert_ne!(position(&app, ball), ball_start);
+}
+
+/// The relative luminance of a color, as used for contrast ratios.
+fn luminance(color: Color) -> f32 {
+    let [r, g, b, _] = color.as_linear_rgba_f32();
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn contrast(a: Color, b: Color) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+#[test]
+fn accessible_colors_are_distinct() {
+    let options = PongOptions::accessible();
+    let background = match options.game.background {
+        Background::Color(color) => color,
+        _ => panic!("the accessible preset should use a plain background"),
+    };
+    for color in [options.player.colors.0, options.player.colors.1, options.ball.color] {
+        assert!(contrast(color, background) >= 4.5);
+    }
+    // Red-green color blindness keeps the blue-yellow axis, so the players have to differ there.
+    let blue_yellow = |color: Color| color.b() - (color.r() + color.g()) / 2.;
+    assert!((blue_yellow(options.player.colors.0) - blue_yellow(options.player.colors.1)).abs() > 0.5);
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let mut source = app(PongOptions::default());
+    for _ in 0..5 {
+        source.update();
+    }
+    let game = pong_game(&mut source);
+    let mut snapshot = capture_snapshot(&mut source.world, game);
+    assert_eq!(snapshot.balls.len(), 1);
+    snapshot.scores = (3, 4);
+    snapshot.paddles.0.y = 50.;
+    snapshot.paddles.1.y = -20.;
+    snapshot.speedup_elapsed = 0.5;
+    snapshot.balls.push(BallState { position: Vec3::new(10., 20., 1.), velocity: Vec2::new(5., -5.) });
+
+    let mut options = PongOptions::default();
+    options.ball.max_balls = 2;
+    let mut target = app(options);
+    let game = pong_game(&mut target);
+    apply_snapshot(&mut target.world, game, &snapshot);
+    let restored = capture_snapshot(&mut target.world, game);
+
+    assert_eq!(restored.scores, (3, 4));
+    assert_eq!(restored.paddles.0, snapshot.paddles.0);
+    assert_eq!(restored.paddles.1, snapshot.paddles.1);
+    assert!((restored.speedup_elapsed - 0.5).abs() < 1e-4);
+    let key = |state: &BallState| (state.position.truncate(), state.velocity);
+    let mut expected: Vec<_> = snapshot.balls.iter().map(key).collect();
+    let mut actual: Vec<_> = restored.balls.iter().map(key).collect();
+    let by_x = |a: &(Vec2, Vec2), b: &(Vec2, Vec2)| a.0.x.partial_cmp(&b.0.x).unwrap();
+    expected.sort_by(by_x);
+    actual.sort_by(by_x);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn snapshot_removes_surplus_balls() {
+    let mut app = app(PongOptions::default());
+    let game = pong_game(&mut app);
+    let mut snapshot = capture_snapshot(&mut app.world, game);
+    snapshot.balls.clear();
+    apply_snapshot(&mut app.world, game, &snapshot);
+    assert!(balls(&mut app).is_empty());
+}
+
+#[test]
+fn snapshot_does_not_exceed_max_balls() {
+    let mut options = PongOptions::default();
+    options.ball.max_balls = 2;
+    let mut app = app(options);
+    let game = pong_game(&mut app);
+    let mut snapshot = capture_snapshot(&mut app.world, game);
+    snapshot.balls = (0..3)
+        .map(|i| BallState { position: Vec3::new(i as f32 * 10., 0., 1.), velocity: Vec2::new(5., 5.) })
+        .collect();
+    apply_snapshot(&mut app.world, game, &snapshot);
+    assert_eq!(balls(&mut app).len(), 2);
+    let restored = capture_snapshot(&mut app.world, game);
+    assert!(restored.balls.iter().all(|state| state.position.x < 20.));
+}
+
+#[test]
+fn speedup_timer_can_be_read_and_reset() {
+    let mut app = app(PongOptions::default());
+    let game = pong_game(&mut app);
+    let start = app.world.get::<BallSpeedupTimer>(game).unwrap().elapsed_secs();
+    for _ in 0..6 {
+        app.update();
+    }
+    let speedup_time = PongOptions::default().ball.speedup_time;
+    let mut timer = app.world.get_mut::<BallSpeedupTimer>(game).unwrap();
+    let elapsed = start + 6. * STEP;
+    assert!((timer.elapsed_secs() - elapsed).abs() < 1e-4);
+    assert!((timer.remaining_secs() - (speedup_time - elapsed)).abs() < 1e-4);
+
+    timer.reset();
+    assert_eq!(timer.elapsed_secs(), 0.);
+    assert_eq!(timer.remaining_secs(), speedup_time);
+}
+
+#[test]
+fn oversized_ball_starts_inside_the_game() {
+    let mut options = PongOptions::default();
+    options.game.size = Vec2::new(600., 120.);
+    options.ball.size = Vec2::new(100., 100.);
+    options.ball.max_balls = 2;
+    let mut app = app(options);
+    app.world.get_resource_mut::<Events<SpawnBallEvent>>().unwrap()
+        .send(SpawnBallEvent { position: Some(Vec2::new(0., 55.)), velocity: Some(Vec2::ZERO), game: None });
+    app.update();
+
+    for ball in balls(&mut app) {
+        let y = position(&app, ball).y;
+        assert!(y + 50. <= 60. && y - 50. >= -60., "ball at {} is not inside the game", y);
+    }
+}
+
+#[test]
+fn ball_larger_than_the_game_is_centered() {
+    let mut options = PongOptions::default();
+    options.game.size = Vec2::new(600., 50.);
+    options.ball.size = Vec2::new(80., 80.);
+    assert_eq!(Ball::start_position(&options).y, 0.);
+    assert_eq!(Ball::clamp_y(&options, 30.), 0.);
+}
+
+#[test]
+fn paddle_can_reach_boundaries() {
+    // Exactly the distance the paddle moves within dt is still reachable.
+    assert!(paddle_can_reach(0., 10., 100., 0.1));
+    assert!(paddle_can_reach(0., -10., 100., 0.1));
+    assert!(!paddle_can_reach(0., 10.5, 100., 0.1));
+    assert!(!paddle_can_reach(0., -10.5, 100., 0.1));
+    // The current position is always reachable, even without time or speed.
+    assert!(paddle_can_reach(5., 5., 100., 0.));
+    assert!(paddle_can_reach(5., 5., 0., 1.));
+    assert!(!paddle_can_reach(5., 6., 0., 1.));
+    // The direction of the speed doesn't matter.
+    assert!(paddle_can_reach(0., 10., -100., 0.1));
+}
+
+fn send<T: Send + Sync + 'static>(app: &mut App, event: T) {
+    app.world.get_resource_mut::<Events<T>>().unwrap().send(event);
+}
+
+#[test]
+fn set_ball_velocity_event_targets_one_or_all_balls() {
+    let mut options = PongOptions::default();
+    options.ball.max_balls = 2;
+    options.ball.max_speed = Some(100.);
+    let mut app = app(options);
+    app.world.insert_resource(FreezeBall(true));
+    send(&mut app, SpawnBallEvent { position: None, velocity: None, game: None });
+    app.update();
+    let balls = balls(&mut app);
+    assert_eq!(balls.len(), 2);
+
+    send(&mut app, SetBallVelocityEvent { entity: Some(balls[0]), velocity: Vec2::new(-40., 10.) });
+    app.update();
+    assert_eq!(velocity(&app, balls[0]), Vec2::new(-40., 10.));
+    assert_ne!(velocity(&app, balls[1]), Vec2::new(-40., 10.));
+
+    send(&mut app, SetBallVelocityEvent { entity: None, velocity: Vec2::new(0., 20.) });
+    app.update();
+    assert_eq!(velocity(&app, balls[0]), Vec2::new(0., 20.));
+    assert_eq!(velocity(&app, balls[1]), Vec2::new(0., 20.));
+
+    // The velocity is limited to the maximum speed.
+    send(&mut app, SetBallVelocityEvent { entity: None, velocity: Vec2::new(300., 0.) });
+    app.update();
+    assert_eq!(velocity(&app, balls[0]), Vec2::new(100., 0.));
+}
+
+#[test]
+fn clamp_speed_limits_both_directions() {
+    let mut ball = BallOptions { min_speed: Some(10.), max_speed: Some(100.), ..Default::default() };
+    assert_eq!(ball.clamp_speed(Vec2::new(300., 400.)), Vec2::new(60., 80.));
+    assert_eq!(ball.clamp_speed(Vec2::new(3., 4.)), Vec2::new(6., 8.));
+    assert_eq!(ball.clamp_speed(Vec2::new(30., 40.)), Vec2::new(30., 40.));
+    // A ball standing still keeps standing still.
+    assert_eq!(ball.clamp_speed(Vec2::ZERO), Vec2::ZERO);
+
+    ball.min_speed = None;
+    ball.max_speed = None;
+    assert_eq!(ball.clamp_speed(Vec2::new(3000., 4000.)), Vec2::new(3000., 4000.));
+}
+
+#[test]
+fn speedup_stops_at_max_speed() {
+    let mut options = PongOptions::default();
+    options.ball.speedup_time = 2. * STEP;
+    options.ball.speedup_factor = 2.;
+    options.ball.max_speed = Some(200.);
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    for _ in 0..30 {
+        app.update();
+        assert!(velocity(&app, ball).length() <= 200. + 1e-3);
+    }
+    assert!((velocity(&app, ball).length() - 200.).abs() < 1e-3);
+}
+
+#[test]
+fn speedup_schedule_does_not_depend_on_the_order() {
+    let mut ball = BallOptions { speedup_factor: 1.1, ..Default::default() };
+    ball.speedup_schedule = vec![(60., 1.5), (f32::NAN, 9.), (30., 1.2)];
+    assert_eq!(ball.speedup_factor_at(10.), 1.1);
+    assert_eq!(ball.speedup_factor_at(30.), 1.2);
+    assert_eq!(ball.speedup_factor_at(90.), 1.5);
+
+    let ball = ball.with_speedup_schedule(vec![(60., 1.5), (f32::NAN, 9.), (30., 1.2)]);
+    assert_eq!(ball.speedup_schedule, vec![(30., 1.2), (60., 1.5)]);
+    assert_eq!(ball.speedup_factor_at(45.), 1.2);
+}
+
+#[test]
+fn friction_stops_at_min_speed() {
+    let mut options = PongOptions::default();
+    options.ball.friction = 0.9;
+    options.ball.min_speed = Some(20.);
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    for _ in 0..120 {
+        app.update();
+        assert!(velocity(&app, ball).length() >= 20. - 1e-3);
+    }
+    assert!((velocity(&app, ball).length() - 20.).abs() < 1e-3);
+}
+
+#[test]
+fn external_controller_moves_paddles() {
+    let mut options = PongOptions::default();
+    options.player.controllers = (Controller::External, Controller::Keyboard);
+    let mut app = app(options);
+    app.world.insert_resource(FreezeBall(true));
+    let paddle1 = paddle(&mut app, Player::Player1);
+    let paddle2 = paddle(&mut app, Player::Player2);
+    let start1 = position(&app, paddle1);
+    let start2 = position(&app, paddle2);
+
+    // The keys of an externally controlled player are ignored.
+    app.world.get_resource_mut::<Input<KeyCode>>().unwrap().press(KeyCode::W);
+    update_after_frame(&mut app);
+    assert_eq!(position(&app, paddle1), start1);
+
+    app.world.get_resource_mut::<Input<KeyCode>>().unwrap().release(KeyCode::W);
+    app.world.insert_resource(PlayerInput(-1., 1.));
+    for _ in 0..3 {
+        update_after_frame(&mut app);
+    }
+    assert!(position(&app, paddle1).y < start1.y);
+    // Player 2 still uses the keyboard.
+    assert_eq!(position(&app, paddle2), start2);
+}
+
+#[test]
+fn leader_is_the_player_ahead() {
+    assert!(leader((3, 1)) == Some(Player::Player1));
+    assert!(leader((0, 2)) == Some(Player::Player2));
+    assert!(leader((4, 4)).is_none());
+    assert!(leader((0, 0)).is_none());
+}
+
+/// The surfaces of the bounces since the previous update.
+fn bounces(app: &App) -> Vec<BounceSurface> {
+    let events = app.world.get_resource::<Events<BounceEvent>>().unwrap();
+    events.get_reader().iter(events).map(|event| event.surface).collect()
+}
+
+#[test]
+fn ball_touching_two_paddles_bounces_once() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    let paddle1 = paddle(&mut app, Player::Player1);
+    let paddle2 = paddle(&mut app, Player::Player2);
+    let paddle1_x = position(&app, paddle1).x;
+    // Player 2 stands right next to player 1, with the ball in between.
+    app.world.get_mut::<Transform>(paddle2).unwrap().translation.x = paddle1_x + 14.;
+    set_ball(&mut app, ball, Vec2::new(paddle1_x + 7., 0.), Vec2::new(-60., 0.));
+
+    app.update();
+    assert_eq!(bounces(&app), vec![BounceSurface::Paddle(Player::Player1)]);
+    assert!(velocity(&app, ball).x > 0.);
+}
+
+#[test]
+fn frozen_speedup_keeps_the_speed() {
+    let mut options = PongOptions::default();
+    options.ball.speedup_time = 2. * STEP;
+    options.ball.speedup_factor = 2.;
+    options.ball.speedup_mode = SpeedupMode::Both;
+    let mut app = app(options);
+    app.world.insert_resource(FreezeSpeedup(true));
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    let paddle_x = position(&app, paddle).x;
+    set_ball(&mut app, ball, Vec2::new(paddle_x + 41., 0.), Vec2::new(-300., 0.));
+
+    for _ in 0..10 {
+        app.update();
+        assert!((velocity(&app, ball).length() - 300.).abs() < 1e-3);
+    }
+    // The ball bounced off the paddle without getting faster.
+    assert!(velocity(&app, ball).x > 0.);
+
+    app.world.insert_resource(FreezeSpeedup(false));
+    app.update();
+    app.update();
+    assert!(velocity(&app, ball).length() > 300.);
+}
+
+#[test]
+fn reset_ball_event_only_resets_the_ball() {
+    let mut app = app(PongOptions::default());
+    app.world.insert_resource(FreezeBall(true));
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    app.world.get_mut::<Transform>(paddle).unwrap().translation.y = 100.;
+    app.world.get_mut::<Score>(paddle).unwrap().0 = 3;
+    app.world.get_mut::<LastTouchedBy>(ball).unwrap().0 = Some(Player::Player1);
+    app.world.get_mut::<PaddleBounceCooldown>(ball).unwrap().0 = PADDLE_BOUNCE_COOLDOWN;
+    app.world.get_mut::<WallBounceCooldown>(ball).unwrap().0 = 1.;
+    set_ball(&mut app, ball, Vec2::new(120., -80.), Vec2::new(-200., 50.));
+
+    send(&mut app, ResetBallEvent { entity: Some(ball) });
+    app.update();
+    assert_eq!(app.world.get::<PaddleBounceCooldown>(ball).unwrap().0, 0);
+    assert_eq!(app.world.get::<WallBounceCooldown>(ball).unwrap().0, 0.);
+    let options = PongOptions::default();
+    assert_eq!(position(&app, ball), Ball::start_position(&options));
+    assert_eq!(velocity(&app, ball), (options.ball.start_velocity)());
+    assert!(last_touched(&app, ball).is_none());
+    assert_eq!(position(&app, paddle).y, 100.);
+    assert_eq!(app.world.get::<Score>(paddle).unwrap().0, 3);
+}
+
+#[test]
+fn every_serve_follows_the_server() {
+    let mut options = PongOptions::default();
+    options.game.serve_rotation = Some(2);
+    // Towards player 1, who serves first.
+    options.ball.start_velocity = || Vec2::new(-30., 15.);
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    assert!(velocity(&app, ball).x > 0.);
+
+    app.world.insert_resource(FreezeBall(true));
+    send(&mut app, ResetBallEvent { entity: None });
+    app.update();
+    assert!(velocity(&app, ball).x > 0.);
+
+    // After two points player 2 serves.
+    let paddle = paddle(&mut app, Player::Player2);
+    app.world.get_mut::<Score>(paddle).unwrap().0 = 2;
+    send(&mut app, ResetBallEvent { entity: None });
+    app.update();
+    assert!(velocity(&app, ball).x < 0.);
+
+    send(&mut app, ResetGameEvent(None));
+    app.update();
+    assert!(velocity(&app, ball).x > 0.);
+}
+
+fn stats(app: &mut App) -> PongStats {
+    let game = pong_game(app);
+    app.world.get::<PongStats>(game).unwrap().clone()
+}
+
+#[test]
+fn stats_count_bounces_and_points() {
+    let mut app = app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    let paddle_x = position(&app, paddle).x;
+    set_ball(&mut app, ball, Vec2::new(paddle_x + 12., 0.), Vec2::new(-300., 0.));
+    app.update();
+    assert_eq!(stats(&mut app).bounces(), 1);
+    assert_eq!(stats(&mut app).longest_rally(), 1);
+    assert!(stats(&mut app).max_ball_speed() >= 300.);
+
+    // Right behind the goal line of player 2.
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    app.update();
+    assert_eq!(stats(&mut app).points_of(&Player::Player1), 1);
+    assert_eq!(stats(&mut app).points_of(&Player::Player2), 0);
+
+    send(&mut app, ResetGameEvent(None));
+    app.update();
+    assert_eq!(stats(&mut app).bounces(), 0);
+    assert_eq!(stats(&mut app).points_of(&Player::Player1), 0);
+}
+
+#[test]
+fn serve_after_goal_starts_at_base_speed() {
+    let mut options = PongOptions::default();
+    options.ball.speedup_time = 4.5 * STEP;
+    options.ball.speedup_factor = 2.;
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    for _ in 0..5 {
+        app.update();
+    }
+    let start_speed = (PongOptions::default().ball.start_velocity)().length();
+    assert!(velocity(&app, ball).length() > start_speed);
+
+    // Right behind the goal line of player 2.
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    // The next speedup is a whole period away, the speed of the last rally is discarded.
+    for _ in 0..4 {
+        app.update();
+        assert!((velocity(&app, ball).length() - start_speed).abs() < 1e-3);
+    }
+}
+
+fn score(app: &mut App, player: Player) -> u16 {
+    let paddle = paddle(app, player);
+    app.world.get::<Score>(paddle).unwrap().0
+}
+
+/// Two balls, which reach the goals of both players in the same step (the one at player 1's goal
+/// is further behind its goal line).
+fn simultaneous_goals(mode: MultiGoalMode) -> App {
+    let mut options = PongOptions::default();
+    options.ball.max_balls = 2;
+    options.game.multi_goal_mode = mode;
+    let mut app = app(options);
+    send(&mut app, SpawnBallEvent { position: None, velocity: None, game: None });
+    app.update();
+    let balls = balls(&mut app);
+    set_ball(&mut app, balls[0], Vec2::new(297., 0.), Vec2::new(300., 0.));
+    set_ball(&mut app, balls[1], Vec2::new(-299., 0.), Vec2::new(-300., 0.));
+    app.update();
+    app
+}
+
+#[test]
+fn simultaneous_goals_all_count() {
+    let mut app = simultaneous_goals(MultiGoalMode::AwardAll);
+    assert_eq!(score(&mut app, Player::Player1), 1);
+    assert_eq!(score(&mut app, Player::Player2), 1);
+}
+
+#[test]
+fn only_the_first_of_simultaneous_goals_counts() {
+    let mut app = simultaneous_goals(MultiGoalMode::FirstOnly);
+    assert_eq!(score(&mut app, Player::Player1), 0);
+    assert_eq!(score(&mut app, Player::Player2), 1);
+    // The other ball was served again.
+    for ball in balls(&mut app) {
+        assert!(position(&app, ball).x.abs() < 100.);
+    }
+}
+
+#[test]
+fn court_and_world_positions_round_trip() {
+    let mut options = PongOptions::default();
+    options.game.position = Vec3::new(100., -50., 0.);
+    let local = Vec2::new(30., 20.);
+    assert_eq!(court_to_world(&options, local), Vec3::new(130., -30., 0.));
+
+    options.game.orientation = Orientation::Vertical;
+    let world = court_to_world(&options, local);
+    // The court is rotated by 90 degrees, its x axis points up.
+    assert!((world - Vec3::new(80., -20., 0.)).length() < 1e-4);
+    assert!((world_to_court(&options, world) - local).length() < 1e-4);
+}
+
+#[test]
+fn oversized_ball_bounces_off_the_walls_at_most_once_per_interval() {
+    let mut options = PongOptions::default();
+    options.game.size = Vec2::new(600., 120.);
+    options.ball.size = Vec2::new(130., 130.);
+    options.ball.wall_bounce_interval = 10. * STEP;
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    set_ball(&mut app, ball, Vec2::ZERO, Vec2::new(0., 300.));
+
+    let mut reader = app.world.get_resource::<Events<BounceEvent>>().unwrap().get_reader();
+    let mut wall_bounces = 0;
+    for _ in 0..30 {
+        app.update();
+        let events = app.world.get_resource::<Events<BounceEvent>>().unwrap();
+        wall_bounces += reader.iter(events).filter(|event| event.surface == BounceSurface::Wall).count();
+    }
+    assert!(wall_bounces <= 3, "{} wall bounces", wall_bounces);
+}
+
+#[test]
+fn ball_speed_text_is_below_the_score() {
+    let mut app = app(PongOptions {
+        score_display_options: Some(ScoreDisplayOptions { show_ball_speed: true, ..Default::default() }),
+        ..Default::default()
+    });
+    let y = |app: &mut App, entity| app.world.get::<Transform>(entity).unwrap().translation.y;
+    let score = app.world.query_filtered::<Entity, With<ScoreDisplayText>>().iter(&app.world).next().unwrap();
+    let speed = app.world.query_filtered::<Entity, With<BallSpeedText>>().iter(&app.world).next().unwrap();
+    let (score_y, speed_y) = (y(&mut app, score), y(&mut app, speed));
+    assert!(speed_y < score_y && speed_y > 0.);
+
+    app.world.get_resource_mut::<PongOptions>().unwrap().game.size = Vec2::new(600., 300.);
+    app.update();
+    assert_eq!(y(&mut app, score) - y(&mut app, speed), score_y - speed_y);
+}
+
+#[test]
+fn score_digits_stay_readable_in_vertical_games() {
+    let mut options = PongOptions::default();
+    options.game.orientation = Orientation::Vertical;
+    options.score_display_options = Some(ScoreDisplayOptions {
+        display: ScoreDisplay::Images { atlas: Handle::default(), digit_size: Vec2::new(20., 30.) },
+        ..Default::default()
+    });
+    let mut app = app(options);
+    let paddle = paddle(&mut app, Player::Player1);
+    app.world.get_mut::<Score>(paddle).unwrap().0 = 12;
+    app.update();
+
+    let digits = |app: &mut App| {
+        let mut digits: Vec<Transform> = app.world.query::<(&ScoreDigit, &Transform)>()
+            .iter(&app.world)
+            .filter(|(ScoreDigit(player), _)| *player == Player::Player1)
+            .map(|(_, trans)| *trans)
+            .collect();
+        digits.sort_by(|a, b| b.translation.y.partial_cmp(&a.translation.y).unwrap());
+        digits
+    };
+    let rotation = Orientation::Vertical.rotation().inverse();
+    let before = digits(&mut app);
+    assert_eq!(before.len(), 2);
+    for trans in before.iter() {
+        assert!(trans.rotation.abs_diff_eq(rotation, 1e-5));
+        // Lined up along the screen's x axis, which is the game's y axis.
+        assert!(trans.translation.x.abs() < 1e-4);
+    }
+    assert!((before[0].translation.y - before[1].translation.y - 20.).abs() < 1e-4);
+
+    // The digits keep their offsets when the game gets resized.
+    app.world.get_resource_mut::<PongOptions>().unwrap().game.size.y += 100.;
+    app.update();
+    let after = digits(&mut app);
+    for (before, after) in before.iter().zip(after.iter()) {
+        assert!((after.translation.y - before.translation.y - 50.).abs() < 1e-4);
+    }
+}
+
+fn events<T: Send + Sync + 'static>(app: &App) -> usize {
+    let events = app.world.get_resource::<Events<T>>().unwrap();
+    events.get_reader().iter(events).count()
+}
+
+#[test]
+fn awarded_points_and_goals_take_the_same_path() {
+    let mut options = PongOptions::default();
+    options.game.win_score = Some(3);
+    let mut app = app(options);
+    let game = pong_game(&mut app);
+
+    assert!(!award_point(&mut app.world, game, Player::Player1));
+    assert_eq!(score(&mut app, Player::Player1), 1);
+    assert_eq!(events::<ScoredPointEvent>(&app), 1);
+    assert!(!award_point(&mut app.world, game, Player::Player1));
+    assert_eq!(events::<MatchPointEvent>(&app), 1);
+    app.update();
+    app.update();
+
+    // A goal of player 1 wins the game.
+    let ball = balls(&mut app)[0];
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    assert_eq!(score(&mut app, Player::Player1), 3);
+    assert_eq!(events::<ScoredPointEvent>(&app), 1);
+    assert_eq!(events::<GameOverEvent>(&app), 1);
+}
+
+#[test]
+fn restart_after_game_over_resets_the_game() {
+    let mut options = PongOptions::default();
+    options.game.win_score = Some(1);
+    options.game.after_game_over = AfterGameOver::WaitForKey { key: KeyCode::Space };
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    app.update();
+    assert!(game_over(&mut app));
+    app.world.get_mut::<PaddleBounceCooldown>(ball).unwrap().0 = PADDLE_BOUNCE_COOLDOWN;
+
+    // A manually pressed key would be cleared before the systems run, so the key event is sent.
+    send(&mut app, KeyboardInput { scan_code: 0, key_code: Some(KeyCode::Space), state: ElementState::Pressed });
+    app.update();
+    app.update();
+    assert!(!game_over(&mut app));
+    assert_eq!(score(&mut app, Player::Player1), 0);
+    assert_eq!(app.world.get::<PaddleBounceCooldown>(ball).unwrap().0, 0);
+    let start = Ball::start_position(&PongOptions::default());
+    assert!((position(&app, ball) - start).length() < 10.);
+}
+
+#[test]
+fn timers_stand_still_while_paused() {
+    let mut options = PongOptions::default();
+    options.game.goal_delay = 2. * STEP;
+    let mut app = app(options);
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player1);
+    send(&mut app, SpeedBoostEvent { player: Player::Player1, multiplier: 2., duration: 0.02, game: None });
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    assert!(app.world.get::<PendingReset>(ball).is_some());
+    assert!(app.world.get::<SpeedBoost>(paddle).is_some());
+
+    app.world.insert_resource(PongPaused(true));
+    for _ in 0..10 {
+        update_after_frame(&mut app);
+    }
+    assert!(app.world.get::<PendingReset>(ball).is_some());
+    assert!(app.world.get::<SpeedBoost>(paddle).is_some());
+
+    app.world.insert_resource(PongPaused(false));
+    for _ in 0..8 {
+        update_after_frame(&mut app);
+    }
+    assert!(app.world.get::<PendingReset>(ball).is_none());
+    assert!(app.world.get::<SpeedBoost>(paddle).is_none());
+}
+
+#[test]
+fn manual_pause_outlasts_the_focus_pause() {
+    let mut options = PongOptions::default();
+    options.game.pause_on_focus_loss = true;
+    let mut app = app(options);
+    let paused = |app: &App| app.world.get_resource::<PongPaused>().unwrap().0;
+    let focus = |app: &mut App, focused| send(app, WindowFocused { id: WindowId::primary(), focused });
+
+    focus(&mut app, false);
+    app.update();
+    assert!(paused(&app));
+    focus(&mut app, true);
+    app.update();
+    assert!(!paused(&app));
+
+    // Paused manually while already paused by the focus loss.
+    focus(&mut app, false);
+    app.update();
+    app.world.insert_resource(PongPaused(true));
+    app.update();
+    focus(&mut app, true);
+    app.update();
+    assert!(paused(&app));
+
+    // Paused manually before the focus loss.
+    app.world.insert_resource(PongPaused(false));
+    app.update();
+    app.world.insert_resource(PongPaused(true));
+    app.update();
+    focus(&mut app, false);
+    app.update();
+    focus(&mut app, true);
+    app.update();
+    assert!(paused(&app));
+}
+
+#[test]
+fn single_player_game_has_a_wall_instead_of_player_2() {
+    let options = PongOptions::single_player();
+    assert!(options.game.single_player);
+    let mut app = app(options);
+    let players: Vec<Player> = app.world.query_filtered::<&Player, IsPlayer>().iter(&app.world).copied().collect();
+    assert!(players == vec![Player::Player1]);
+
+    let ball = balls(&mut app)[0];
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    assert!(velocity(&app, ball).x < 0.);
+    assert_eq!(score(&mut app, Player::Player1), 0);
+}
+
+#[test]
+fn escalation_uses_the_physics_step() {
+    let mut options = PongOptions::default();
+    options.game.escalation = Some(3.5 * STEP);
+    options.ball.max_balls = 2;
+    let mut app = app(options);
+    // Without waiting between the updates, hardly any real time passes.
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(balls(&mut app).len(), 1);
+    app.update();
+    app.update();
+    assert_eq!(balls(&mut app).len(), 2);
+}
+
+/// A headless game with interpolated positions, in which the physics run with a real fixed
+/// timestep of 0.05 seconds (about every tenth `update_after_frame`).
+fn interpolated_app(mut options: PongOptions) -> App {
+    options.game.interpolate = true;
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(WindowPlugin::default())
+        .insert_resource(options)
+        .add_plugin(PongPlugin::new().with_physics_schedule(PhysicsSchedule::FixedTimestep(0.05)));
+    app.update();
+    app
+}
+
+#[test]
+fn match_timer_uses_the_physics_step() {
+    let mut options = PongOptions::default();
+    options.game.time_limit = Some(3.5 * STEP);
+    let mut app = app(options);
+    // Without waiting between the updates, hardly any real time passes.
+    for _ in 0..2 {
+        app.update();
+    }
+    assert!(!game_over(&mut app));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(game_over(&mut app));
+}
+
+#[test]
+fn game_does_not_run_before_the_first_serve() {
+    let mut options = PongOptions::default();
+    options.game.wait_for_ready = true;
+    options.game.time_limit = Some(3.5 * STEP);
+    options.game.escalation = Some(2.5 * STEP);
+    options.ball.max_balls = 2;
+    let mut app = app(options);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(!game_over(&mut app));
+    let game = pong_game(&mut app);
+    assert_eq!(app.world.get::<MatchElapsed>(game).unwrap().elapsed_secs(), 0.);
+    assert_eq!(balls(&mut app).len(), 1);
+
+    send(&mut app, KeyboardInput { scan_code: 0, key_code: Some(KeyCode::W), state: ElementState::Pressed });
+    for _ in 0..6 {
+        app.update();
+    }
+    assert_eq!(balls(&mut app).len(), 2);
+    assert!(game_over(&mut app));
+}
+
+#[test]
+fn interpolated_paddles_are_rendered_behind_their_actual_position() {
+    let mut app = interpolated_app(PongOptions::default());
+    let paddle = paddle(&mut app, Player::Player1);
+    let start = position(&app, paddle);
+
+    app.world.get_resource_mut::<Input<KeyCode>>().unwrap().press(KeyCode::W);
+    for _ in 0..15 {
+        update_after_frame(&mut app);
+    }
+    let actual = app.world.get::<Interpolation>(paddle).unwrap().actual;
+    assert!(actual.y > start.y);
+    assert!(position(&app, paddle).y < actual.y);
+    assert!(position(&app, paddle).y >= start.y);
+}
+
+#[test]
+fn interpolated_ball_snaps_to_the_serve_after_a_goal() {
+    let mut app = interpolated_app(PongOptions::default());
+    let ball = balls(&mut app)[0];
+    // Passes above the paddle of player 2 and reaches the goal line in the next physics step.
+    set_ball(&mut app, ball, Vec2::new(285., 100.), Vec2::new(600., 0.));
+
+    let mut frames_after_goal = 0;
+    for _ in 0..60 {
+        update_after_frame(&mut app);
+        if score(&mut app, Player::Player1) == 0 {
+            continue;
+        }
+        frames_after_goal += 1;
+        // The ball isn't rendered on its way back from the goal line to the center.
+        let x = position(&app, ball).x;
+        assert!(x.abs() < 20., "ball rendered at {} after the goal", x);
+    }
+    assert!(frames_after_goal > 0);
+}
+
+#[test]
+fn shrinking_the_game_keeps_paddles_and_balls_inside() {
+    let mut app = app(PongOptions::default());
+    app.world.insert_resource(FreezeBall(true));
+    let ball = balls(&mut app)[0];
+    let paddle = paddle(&mut app, Player::Player2);
+    app.world.get_mut::<Transform>(paddle).unwrap().translation.y = 170.;
+    set_ball(&mut app, ball, Vec2::new(-250., -180.), Vec2::ZERO);
+
+    app.world.get_resource_mut::<PongOptions>().unwrap().game.size = Vec2::new(400., 200.);
+    app.update();
+    let options = app.world.get_resource::<PongOptions>().unwrap().clone();
+    assert_eq!(position(&app, paddle).y, (200. - options.player.size.y) / 2.);
+    // In front of player 1's paddle and touching the bottom.
+    let half_ball = options.ball.collision_size() / 2.;
+    let paddle_front = 200. - options.player.size.x * 1.5;
+    assert_eq!(position(&app, ball).truncate(), Vec2::new(-paddle_front + half_ball.x, -100. + half_ball.y));
+    assert_eq!(score(&mut app, Player::Player2), 0);
+}
+
+#[test]
+fn games_side_by_side_are_independent() {
+    let mut app = app(PongOptions::default());
+    let first = pong_game(&mut app);
+    let mut options = PongOptions::default();
+    options.game.position = Vec3::new(0., 500., 0.);
+    options.ball.max_balls = 2;
+    let second = app.world.spawn().insert_bundle(PongGameBundle::new(options)).id();
+    app.update();
+    let balls_of = |app: &mut App, game: Entity| -> Vec<Entity> {
+        app.world.query_filtered::<(Entity, &Parent), IsBall>()
+            .iter(&app.world)
+            .filter(|(_, parent)| parent.0 == game)
+            .map(|(ball, _)| ball)
+            .collect()
+    };
+    let scores = |app: &mut App, game: Entity| capture_snapshot(&mut app.world, game).scores;
+    assert_eq!(balls_of(&mut app, second).len(), 1);
+
+    // Right behind the goal line of player 2 in the first game.
+    let ball = balls_of(&mut app, first)[0];
+    set_ball(&mut app, ball, Vec2::new(299., 0.), Vec2::new(300., 0.));
+    app.update();
+    assert_eq!(scores(&mut app, first), (1, 0));
+    assert_eq!(scores(&mut app, second), (0, 0));
+
+    // Requests for one game leave the other one alone.
+    send(&mut app, SpawnBallEvent { position: None, velocity: None, game: Some(second) });
+    app.update();
+    assert_eq!(balls_of(&mut app, first).len(), 1);
+    assert_eq!(balls_of(&mut app, second).len(), 2);
+
+    award_point(&mut app.world, second, Player::Player2);
+    send(&mut app, ResetGameEvent(Some(first)));
+    app.update();
+    assert_eq!(scores(&mut app, first), (0, 0));
+    assert_eq!(scores(&mut app, second), (0, 1));
+}
+
+#[test]
+fn rounded_corners_render_a_mesh_instead_of_the_sprite() {
+    let mut options = PongOptions::default();
+    options.ball.corner_radius = 7.5;
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .add_plugin(InputPlugin)
+        .add_plugin(WindowPlugin::default())
+        .insert_resource(options)
+        .add_plugin(PongPlugin::new());
+    app.world.insert_resource(PhysicsStep(Some(STEP)));
+    app.update();
+    app.update();
+    let rounded = |app: &mut App| -> Vec<Entity> {
+        app.world.query_filtered::<&Parent, With<RoundedCorners>>().iter(&app.world).map(|parent| parent.0).collect()
+    };
+
+    // Only the ball is rounded, the paddles keep their sharp sprites.
+    let ball = balls(&mut app)[0];
+    assert_eq!(rounded(&mut app), vec![ball]);
+    assert!(!app.world.get::<Visibility>(ball).unwrap().is_visible);
+
+    app.world.get_resource_mut::<PongOptions>().unwrap().ball.corner_radius = 0.;
+    app.update();
+    app.update();
+    assert!(rounded(&mut app).is_empty());
+    assert!(app.world.get::<Visibility>(ball).unwrap().is_visible);
+}
+
+#[test]
+fn rounded_rect_stays_inside_its_size() {
+    let mesh = rounded_rect(Vec2::new(10., 50.), 20.);
+    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+        _ => panic!("missing positions"),
+    };
+    assert_eq!(positions.len(), 1 + 4 * (CORNER_SEGMENTS + 1));
+    // The radius is limited to half the width, so the short sides are fully round.
+    assert!(positions.iter().all(|[x, y, _]| x.abs() <= 5. + 1e-4 && y.abs() <= 25. + 1e-4));
+    assert!(positions.iter().any(|[x, y, _]| x.abs() < 1e-4 && (y.abs() - 25.).abs() < 1e-4));
+}